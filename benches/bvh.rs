@@ -0,0 +1,66 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::f64::consts::PI;
+
+use ray::obj::parse_obj_file;
+use ray::transformations::view_transform;
+use ray::{Camera, Group, Ray, Shape, Tuple};
+
+// A UV sphere written out as OBJ, `slices * stacks` quads (each fanned
+// into two triangles by the loader).
+fn sphere_mesh(slices: usize, stacks: usize) -> String {
+    let mut obj = String::new();
+    for i in 0..=stacks {
+        let phi = PI * i as f64 / stacks as f64;
+        for j in 0..slices {
+            let theta = 2.0 * PI * j as f64 / slices as f64;
+            obj.push_str(&format!(
+                "v {} {} {}\n",
+                phi.sin() * theta.cos(),
+                phi.cos(),
+                phi.sin() * theta.sin()
+            ));
+        }
+    }
+    for i in 0..stacks {
+        for j in 0..slices {
+            let a = i * slices + j + 1;
+            let b = i * slices + (j + 1) % slices + 1;
+            obj.push_str(&format!("f {} {} {} {}\n", a, b, b + slices, a + slices));
+        }
+    }
+    obj
+}
+
+fn rays() -> Vec<Ray> {
+    let mut camera = Camera::camera(40, 40, PI / 3.0);
+    camera.set_transform(view_transform(
+        Tuple::point(0.0, 0.0, -3.0),
+        Tuple::point(0.0, 0.0, 0.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    ));
+    (0..40)
+        .flat_map(|y| (0..40).map(move |x| (x, y)))
+        .map(|(x, y)| camera.ray_for_pixel(x, y))
+        .collect()
+}
+
+fn intersect_all(group: &Group, rays: &[Ray]) -> usize {
+    rays.iter().map(|r| group.intersect(r).len()).sum()
+}
+
+fn bench_bvh(c: &mut Criterion) {
+    let obj = sphere_mesh(100, 50);
+    let flat = parse_obj_file(&obj).unwrap().to_group();
+    let mut divided = parse_obj_file(&obj).unwrap().to_group();
+    divided.divide(8);
+    let rays = rays();
+
+    let mut group = c.benchmark_group("mesh of 10000 triangles, 1600 rays");
+    group.sample_size(10);
+    group.bench_function("flat", |b| b.iter(|| intersect_all(&flat, &rays)));
+    group.bench_function("divided", |b| b.iter(|| intersect_all(&divided, &rays)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_bvh);
+criterion_main!(benches);
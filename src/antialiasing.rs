@@ -0,0 +1,275 @@
+use crate::colors::Color;
+use crate::sequences::{RandomSequence, Sequence};
+
+/// Reconstruction filters, weighting each sample by its distance from the
+/// pixel center. Distances are in pixels and the filters are applied
+/// separately along x and y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// Equal weights over the pixel itself.
+    Box,
+    /// Weights falling linearly to zero one pixel out.
+    Tent,
+    /// A truncated Gaussian reaching 1.5 pixels out.
+    Gaussian,
+    /// The Mitchell-Netravali cubic (B = C = 1/3), reaching 2 pixels out.
+    /// Sharper than the Gaussian, with small negative lobes.
+    Mitchell,
+}
+
+const GAUSSIAN_ALPHA: f64 = 2.0;
+
+impl Filter {
+    /// How far from the pixel center samples are taken.
+    pub fn radius(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    /// The weight of a sample `d` pixels from the center along one axis.
+    pub fn weight(&self, d: f64) -> f64 {
+        let d = d.abs();
+        if d > self.radius() {
+            return 0.0;
+        }
+        match self {
+            Filter::Box => 1.0,
+            Filter::Tent => 1.0 - d,
+            Filter::Gaussian => {
+                let r = self.radius();
+                (-GAUSSIAN_ALPHA * d * d).exp() - (-GAUSSIAN_ALPHA * r * r).exp()
+            }
+            Filter::Mitchell => {
+                let (b, c) = (1.0 / 3.0, 1.0 / 3.0);
+                let w = if d < 1.0 {
+                    (12.0 - 9.0 * b - 6.0 * c) * d.powi(3) + (-18.0 + 12.0 * b + 6.0 * c) * d * d + (6.0 - 2.0 * b)
+                } else {
+                    (-b - 6.0 * c) * d.powi(3) + (6.0 * b + 30.0 * c) * d * d + (-12.0 * b - 48.0 * c) * d
+                        + (8.0 * b + 24.0 * c)
+                };
+                w / 6.0
+            }
+        }
+    }
+}
+
+/// Where a pixel's samples are placed within the filter's reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleLayout {
+    /// One sample at a random spot in each cell of a grid.
+    Stratified,
+    /// Samples anywhere, independently of each other.
+    Random,
+}
+
+/// How many rays are cast for each pixel and how they are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Antialiasing {
+    /// Samples along each axis, so a pixel gets `samples * samples` rays.
+    /// A single sample means one ray through the pixel center, as without
+    /// antialiasing.
+    pub samples: usize,
+    pub layout: SampleLayout,
+    pub filter: Filter,
+    /// When set, pixels are first rendered with a single ray, and only those
+    /// whose color differs from a neighbour's by more than this (in any
+    /// channel) are supersampled.
+    pub adaptive_threshold: Option<f64>,
+    /// Seeds the jitter of both the samples and the lights, which is
+    /// reproducible pixel by pixel.
+    pub seed: u64,
+}
+
+impl Antialiasing {
+    /// One ray per pixel.
+    pub fn antialiasing() -> Antialiasing {
+        Antialiasing {
+            samples: 1,
+            layout: SampleLayout::Stratified,
+            filter: Filter::Box,
+            adaptive_threshold: None,
+            seed: 0,
+        }
+    }
+
+    /// Stratified supersampling with `samples * samples` rays per pixel.
+    pub fn supersampled(samples: usize) -> Antialiasing {
+        Antialiasing {
+            samples,
+            ..Antialiasing::antialiasing()
+        }
+    }
+
+    /// The sample positions for pixel (`x`, `y`), as offsets from its center
+    /// in pixels.
+    pub fn offsets(&self, x: usize, y: usize, width: usize) -> Vec<(f64, f64)> {
+        if self.samples <= 1 {
+            return vec![(0.0, 0.0)];
+        }
+        let mut jitter = RandomSequence::random_sequence(self.pixel_seed(x, y, width));
+
+        let n = self.samples;
+        let r = self.filter.radius();
+        let mut offsets = Vec::with_capacity(n * n);
+        for j in 0..n {
+            for i in 0..n {
+                let (u, v) = match self.layout {
+                    SampleLayout::Stratified => (
+                        (i as f64 + jitter.next()) / n as f64,
+                        (j as f64 + jitter.next()) / n as f64,
+                    ),
+                    SampleLayout::Random => (jitter.next(), jitter.next()),
+                };
+                offsets.push(((2.0 * u - 1.0) * r, (2.0 * v - 1.0) * r));
+            }
+        }
+        offsets
+    }
+
+    /// The sequence that jittered lights are sampled with while shading
+    /// pixel (`x`, `y`).
+    pub fn light_sampler(&self, x: usize, y: usize, width: usize) -> RandomSequence {
+        // kept apart from the stream the offsets are drawn from
+        RandomSequence::random_sequence(self.pixel_seed(x, y, width) ^ 0x6a09_e667_f3bc_c909)
+    }
+
+    // A stream of its own for every pixel keeps the result independent of
+    // the order pixels are rendered in.
+    fn pixel_seed(&self, x: usize, y: usize, width: usize) -> u64 {
+        let index = (y * width + x) as u64;
+        self.seed ^ index.wrapping_mul(0xd1b5_4a32_d192_ed03)
+    }
+
+    /// Combines the colors seen at `offsets` with the filter's weights.
+    pub fn reconstruct(&self, offsets: &[(f64, f64)], colors: &[Color]) -> Color {
+        let mut sum = Color::color(0.0, 0.0, 0.0);
+        let mut total = 0.0;
+        for ((dx, dy), c) in offsets.iter().zip(colors) {
+            let w = self.filter.weight(*dx) * self.filter.weight(*dy);
+            sum = sum + *c * w;
+            total += w;
+        }
+        if total.abs() < 1e-9 {
+            // only possible with negative lobes and very few samples
+            let sum = colors.iter().fold(Color::color(0.0, 0.0, 0.0), |acc, c| acc + *c);
+            return sum * (1.0 / colors.len() as f64);
+        }
+        sum * (1.0 / total)
+    }
+}
+
+/// The largest difference between two colors in any channel.
+pub fn color_difference(a: Color, b: Color) -> f64 {
+    (a.red - b.red).abs().max((a.green - b.green).abs()).max((a.blue - b.blue).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuples::equal;
+
+    #[test]
+    fn test_fn() {
+        let aa = Antialiasing::antialiasing();
+        assert_eq!(aa.samples, 1);
+        assert_eq!(aa.offsets(3, 4, 10), vec![(0.0, 0.0)]);
+        assert_eq!(Antialiasing::supersampled(4).samples, 4);
+    }
+
+    #[test]
+    fn test_filter_weights() {
+        assert!(equal(Filter::Box.weight(0.4), 1.0));
+        assert!(equal(Filter::Box.weight(0.6), 0.0));
+        assert!(equal(Filter::Tent.weight(-0.25), 0.75));
+        assert!(equal(Filter::Tent.weight(1.5), 0.0));
+        assert!(equal(Filter::Gaussian.weight(0.0), 1.0 - (-4.5_f64).exp()));
+        assert!(equal(Filter::Gaussian.weight(1.5), 0.0));
+        assert!(equal(Filter::Mitchell.weight(0.0), 8.0 / 9.0));
+        // continuous where the two cubics meet, and zero at the edge
+        assert!(equal(Filter::Mitchell.weight(1.0 - 1e-9), 1.0 / 18.0));
+        assert!(equal(Filter::Mitchell.weight(1.0), 1.0 / 18.0));
+        assert!(equal(Filter::Mitchell.weight(2.0), 0.0));
+        assert!(Filter::Mitchell.weight(1.5) < 0.0);
+    }
+
+    #[test]
+    fn test_stratified_offsets() {
+        let aa = Antialiasing {
+            filter: Filter::Tent,
+            ..Antialiasing::supersampled(3)
+        };
+        let offsets = aa.offsets(2, 1, 5);
+        assert_eq!(offsets.len(), 9);
+        // one sample in each cell of a 3x3 grid spanning -1..1
+        for (k, (dx, dy)) in offsets.iter().enumerate() {
+            let (i, j) = ((k % 3) as f64, (k / 3) as f64);
+            assert!((-1.0 + i * 2.0 / 3.0..=-1.0 + (i + 1.0) * 2.0 / 3.0).contains(dx));
+            assert!((-1.0 + j * 2.0 / 3.0..=-1.0 + (j + 1.0) * 2.0 / 3.0).contains(dy));
+        }
+        // reproducible per pixel, different between pixels
+        assert_eq!(aa.offsets(2, 1, 5), offsets);
+        assert_ne!(aa.offsets(3, 1, 5), offsets);
+        let reseeded = Antialiasing { seed: 7, ..aa };
+        assert_ne!(reseeded.offsets(2, 1, 5), offsets);
+    }
+
+    #[test]
+    fn test_light_sampler() {
+        let aa = Antialiasing::antialiasing();
+        let first = |mut s: RandomSequence| s.next();
+        assert_eq!(first(aa.light_sampler(2, 1, 5)), first(aa.light_sampler(2, 1, 5)));
+        assert_ne!(first(aa.light_sampler(2, 1, 5)), first(aa.light_sampler(3, 1, 5)));
+        let reseeded = Antialiasing { seed: 7, ..aa };
+        assert_ne!(first(reseeded.light_sampler(2, 1, 5)), first(aa.light_sampler(2, 1, 5)));
+    }
+
+    #[test]
+    fn test_random_offsets() {
+        let aa = Antialiasing {
+            layout: SampleLayout::Random,
+            ..Antialiasing::supersampled(4)
+        };
+        let offsets = aa.offsets(0, 0, 1);
+        assert_eq!(offsets.len(), 16);
+        assert!(offsets.iter().all(|(dx, dy)| dx.abs() <= 0.5 && dy.abs() <= 0.5));
+    }
+
+    #[test]
+    fn test_reconstruct() {
+        let white = Color::color(1.0, 1.0, 1.0);
+        let black = Color::color(0.0, 0.0, 0.0);
+        let aa = Antialiasing::supersampled(2);
+        let offsets = [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)];
+        let c = aa.reconstruct(&offsets, &[white, white, black, black]);
+        assert!(c.approx_eq(&Color::color(0.5, 0.5, 0.5)));
+
+        // samples nearer the center count for more
+        let aa = Antialiasing {
+            filter: Filter::Tent,
+            ..aa
+        };
+        let c = aa.reconstruct(&[(0.0, 0.0), (0.75, 0.0)], &[white, black]);
+        assert!(c.approx_eq(&Color::color(0.8, 0.8, 0.8)));
+
+        // a uniform color comes back unchanged whatever the weights
+        let aa = Antialiasing {
+            filter: Filter::Mitchell,
+            ..aa
+        };
+        let offsets = aa.offsets(0, 0, 1);
+        let colors = vec![Color::color(0.2, 0.4, 0.6); offsets.len()];
+        assert!(aa.reconstruct(&offsets, &colors).approx_eq(&Color::color(0.2, 0.4, 0.6)));
+    }
+
+    #[test]
+    fn test_color_difference() {
+        let a = Color::color(0.1, 0.5, 0.9);
+        let b = Color::color(0.2, 0.1, 1.0);
+        assert!(equal(color_difference(a, b), 0.4));
+        assert!(equal(color_difference(a, a), 0.0));
+    }
+}
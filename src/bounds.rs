@@ -0,0 +1,261 @@
+use crate::cubes::check_axis;
+use crate::matrices::Matrix;
+use crate::rays::Ray;
+use crate::tuples::Tuple;
+
+/// An axis-aligned box, used to skip whole groups of shapes a ray cannot hit.
+///
+/// Bounds may be infinite (planes, uncapped cylinders), and a box with no
+/// points added yet is empty: its `min` is above its `max` on every axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Tuple,
+    pub max: Tuple,
+}
+
+impl BoundingBox {
+    pub fn bounding_box(min: Tuple, max: Tuple) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    pub fn empty() -> BoundingBox {
+        BoundingBox {
+            min: Tuple::point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Tuple::point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn add_point(&mut self, p: Tuple) {
+        self.min = Tuple::point(self.min.x.min(p.x), self.min.y.min(p.y), self.min.z.min(p.z));
+        self.max = Tuple::point(self.max.x.max(p.x), self.max.y.max(p.y), self.max.z.max(p.z));
+    }
+
+    /// Whether no point has been added to the box.
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Grows this box to also enclose `other`.
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if other.is_empty() {
+            return;
+        }
+        self.add_point(other.min);
+        self.add_point(other.max);
+    }
+
+    pub fn contains_point(&self, p: Tuple) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    /// The axis-aligned box enclosing this one once transformed by `m`.
+    pub fn transform(&self, m: &Matrix) -> BoundingBox {
+        // its infinite corners would otherwise make it span all of space
+        if self.is_empty() {
+            return *self;
+        }
+        let mut bounds = BoundingBox::empty();
+        for x in [self.min.x, self.max.x] {
+            for y in [self.min.y, self.max.y] {
+                for z in [self.min.z, self.max.z] {
+                    bounds.add_point(transform_corner(m, [x, y, z]));
+                }
+            }
+        }
+        bounds
+    }
+
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+        tmin <= tmax && tmax >= 0.0
+    }
+
+    /// Cuts the box in half across its largest dimension.
+    pub fn split(&self) -> (BoundingBox, BoundingBox) {
+        let dx = self.max.x - self.min.x;
+        let dy = self.max.y - self.min.y;
+        let dz = self.max.z - self.min.z;
+        let greatest = dx.max(dy).max(dz);
+
+        let (mut mid_min, mut mid_max) = (self.min, self.max);
+        if greatest == dx {
+            mid_min.x = self.min.x + dx / 2.0;
+            mid_max.x = mid_min.x;
+        } else if greatest == dy {
+            mid_min.y = self.min.y + dy / 2.0;
+            mid_max.y = mid_min.y;
+        } else {
+            mid_min.z = self.min.z + dz / 2.0;
+            mid_max.z = mid_min.z;
+        }
+
+        (
+            BoundingBox::bounding_box(self.min, mid_max),
+            BoundingBox::bounding_box(mid_min, self.max),
+        )
+    }
+}
+
+impl Default for BoundingBox {
+    fn default() -> BoundingBox {
+        BoundingBox::empty()
+    }
+}
+
+// Like `m * point`, but skips zero entries so that infinite corners do not
+// turn into NaN; an axis that still mixes both infinities becomes unbounded.
+fn transform_corner(m: &Matrix, corner: [f64; 3]) -> Tuple {
+    let row = |r: usize| {
+        let mut v = m[(r, 3)];
+        for (c, x) in corner.iter().enumerate() {
+            if m[(r, c)] != 0.0 {
+                v += m[(r, c)] * x;
+            }
+        }
+        v
+    };
+    let (x, y, z) = (row(0), row(1), row(2));
+    let unbounded = |v: f64, inf: f64| if v.is_nan() { inf } else { v };
+    // the opposite corner resolves to the other infinity
+    Tuple::point(
+        unbounded(x, if corner[0] < 0.0 { f64::NEG_INFINITY } else { f64::INFINITY }),
+        unbounded(y, if corner[1] < 0.0 { f64::NEG_INFINITY } else { f64::INFINITY }),
+        unbounded(z, if corner[2] < 0.0 { f64::NEG_INFINITY } else { f64::INFINITY }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformations::{rotation_x, rotation_y};
+    use std::f64::consts::{FRAC_PI_4, SQRT_2};
+
+    fn bbox(min: (f64, f64, f64), max: (f64, f64, f64)) -> BoundingBox {
+        BoundingBox::bounding_box(Tuple::point(min.0, min.1, min.2), Tuple::point(max.0, max.1, max.2))
+    }
+
+    #[test]
+    fn test_empty() {
+        // tuples compare with a tolerance, which infinities never satisfy
+        let b = BoundingBox::empty();
+        assert!([b.min.x, b.min.y, b.min.z].iter().all(|v| *v == f64::INFINITY));
+        assert!([b.max.x, b.max.y, b.max.z].iter().all(|v| *v == f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn test_add_point() {
+        let mut b = BoundingBox::empty();
+        b.add_point(Tuple::point(-5.0, 2.0, 0.0));
+        b.add_point(Tuple::point(7.0, 0.0, -3.0));
+        assert_eq!(b, bbox((-5.0, 0.0, -3.0), (7.0, 2.0, 0.0)));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut b1 = bbox((-5.0, -2.0, 0.0), (7.0, 4.0, 4.0));
+        let b2 = bbox((8.0, -7.0, -2.0), (14.0, 2.0, 8.0));
+        b1.merge(&b2);
+        assert_eq!(b1, bbox((-5.0, -7.0, -2.0), (14.0, 4.0, 8.0)));
+        b1.merge(&BoundingBox::empty());
+        assert_eq!(b1, bbox((-5.0, -7.0, -2.0), (14.0, 4.0, 8.0)));
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let b = bbox((5.0, -2.0, 0.0), (11.0, 4.0, 7.0));
+        let cases = [
+            ((5.0, -2.0, 0.0), true),
+            ((11.0, 4.0, 7.0), true),
+            ((8.0, 1.0, 3.0), true),
+            ((3.0, 0.0, 3.0), false),
+            ((8.0, -4.0, 3.0), false),
+            ((8.0, 1.0, -1.0), false),
+            ((13.0, 1.0, 3.0), false),
+            ((8.0, 5.0, 3.0), false),
+            ((8.0, 1.0, 8.0), false),
+        ];
+        for ((x, y, z), expected) in cases {
+            assert_eq!(b.contains_point(Tuple::point(x, y, z)), expected);
+        }
+    }
+
+    #[test]
+    fn test_contains_box() {
+        let b = bbox((5.0, -2.0, 0.0), (11.0, 4.0, 7.0));
+        assert!(b.contains_box(&bbox((5.0, -2.0, 0.0), (11.0, 4.0, 7.0))));
+        assert!(b.contains_box(&bbox((6.0, -1.0, 1.0), (10.0, 3.0, 6.0))));
+        assert!(!b.contains_box(&bbox((4.0, -3.0, -1.0), (10.0, 3.0, 6.0))));
+        assert!(!b.contains_box(&bbox((6.0, -1.0, 1.0), (12.0, 5.0, 8.0))));
+    }
+
+    #[test]
+    fn test_transform() {
+        let b = bbox((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0));
+        let b2 = b.transform(&(rotation_x(FRAC_PI_4) * rotation_y(FRAC_PI_4)));
+        assert!(b2.min.approx_eq(&Tuple::point(-SQRT_2, -1.70710, -1.70710)));
+        assert!(b2.max.approx_eq(&Tuple::point(SQRT_2, 1.70710, 1.70710)));
+    }
+
+    #[test]
+    fn test_transform_infinite() {
+        let plane = bbox((f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY), (f64::INFINITY, 0.0, f64::INFINITY));
+        let b = plane.transform(&rotation_y(FRAC_PI_4));
+        assert_eq!(b.min.y, 0.0);
+        assert_eq!(b.max.y, 0.0);
+        assert!(b.min.x == f64::NEG_INFINITY && b.max.x == f64::INFINITY);
+        assert!(b.min.z == f64::NEG_INFINITY && b.max.z == f64::INFINITY);
+    }
+
+    #[test]
+    fn test_transform_empty() {
+        let b = BoundingBox::empty().transform(&(rotation_x(FRAC_PI_4) * rotation_y(FRAC_PI_4)));
+        assert!(b.is_empty());
+        assert!(!bbox((0.0, 0.0, 0.0), (0.0, 0.0, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn test_intersects() {
+        let b = bbox((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0));
+        let cases = [
+            ((5.0, 0.5, 0.0), (-1.0, 0.0, 0.0), true),
+            ((-5.0, 0.5, 0.0), (1.0, 0.0, 0.0), true),
+            ((0.5, 5.0, 0.0), (0.0, -1.0, 0.0), true),
+            ((0.0, 0.5, 0.0), (0.0, 0.0, 1.0), true),
+            ((-2.0, 0.0, 0.0), (2.0, 4.0, 6.0), false),
+            ((0.0, -2.0, 0.0), (6.0, 2.0, 4.0), false),
+            ((2.0, 2.0, 0.0), (-1.0, 0.0, 0.0), false),
+            // the box is behind the ray
+            ((0.0, 0.0, 5.0), (0.0, 0.0, 1.0), false),
+        ];
+        for ((ox, oy, oz), (dx, dy, dz), expected) in cases {
+            let r = Ray::ray(Tuple::point(ox, oy, oz), Tuple::vector(dx, dy, dz).normalize());
+            assert_eq!(b.intersects(&r), expected);
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let (left, right) = bbox((-1.0, -4.0, -5.0), (9.0, 6.0, 5.0)).split();
+        assert_eq!(left, bbox((-1.0, -4.0, -5.0), (4.0, 6.0, 5.0)));
+        assert_eq!(right, bbox((4.0, -4.0, -5.0), (9.0, 6.0, 5.0)));
+
+        let (left, right) = bbox((-1.0, -2.0, -3.0), (9.0, 5.5, 3.0)).split();
+        assert_eq!(left, bbox((-1.0, -2.0, -3.0), (4.0, 5.5, 3.0)));
+        assert_eq!(right, bbox((4.0, -2.0, -3.0), (9.0, 5.5, 3.0)));
+
+        let (left, right) = bbox((-1.0, -2.0, -3.0), (5.0, 8.0, 3.0)).split();
+        assert_eq!(left, bbox((-1.0, -2.0, -3.0), (5.0, 3.0, 3.0)));
+        assert_eq!(right, bbox((-1.0, 3.0, -3.0), (5.0, 8.0, 3.0)));
+    }
+}
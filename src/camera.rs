@@ -0,0 +1,479 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{panic, thread};
+
+use crate::antialiasing::{color_difference, Antialiasing};
+use crate::canvas::Canvas;
+use crate::colors::Color;
+use crate::matrices::Matrix;
+use crate::rays::Ray;
+use crate::tuples::Tuple;
+use crate::world::World;
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// How many rays are cast per pixel; one through the center by default.
+    pub antialiasing: Antialiasing,
+    /// How many threads render at once; 0 uses every available core. The
+    /// image is the same whatever the count, since every pixel's jitter comes
+    /// from a sequence of its own.
+    pub threads: usize,
+    /// The edge length, in pixels, of the square tiles handed to threads.
+    pub tile_size: usize,
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    transform: Matrix,
+    inverse: Matrix,
+    pixel_size: f64,
+    half_width: f64,
+    half_height: f64,
+}
+
+impl Camera {
+    pub fn camera(hsize: usize, vsize: usize, field_of_view: f64) -> Camera {
+        let mut c = Camera {
+            antialiasing: Antialiasing::antialiasing(),
+            threads: 0,
+            tile_size: 16,
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Matrix::identity(),
+            inverse: Matrix::identity(),
+            pixel_size: 0.0,
+            half_width: 0.0,
+            half_height: 0.0,
+        };
+        c.update_view();
+        c
+    }
+
+    // works out the extent of the canvas one unit in front of the camera
+    fn update_view(&mut self) {
+        let half_view = (self.field_of_view / 2.0).tan();
+        let aspect = self.hsize as f64 / self.vsize as f64;
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+        self.half_width = half_width;
+        self.half_height = half_height;
+        self.pixel_size = half_width * 2.0 / self.hsize as f64;
+    }
+
+    pub fn hsize(&self) -> usize {
+        self.hsize
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.vsize
+    }
+
+    /// Resizes the canvas in pixels, keeping the field of view.
+    pub fn set_size(&mut self, hsize: usize, vsize: usize) {
+        self.hsize = hsize;
+        self.vsize = vsize;
+        self.update_view();
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    pub fn set_field_of_view(&mut self, field_of_view: f64) {
+        self.field_of_view = field_of_view;
+        self.update_view();
+    }
+
+    /// The width of a pixel one unit in front of the camera.
+    pub fn pixel_size(&self) -> f64 {
+        self.pixel_size
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.transform
+    }
+
+    /// Sets the view transform, caching its inverse. Panics if the matrix is not invertible.
+    pub fn set_transform(&mut self, m: Matrix) {
+        self.inverse = m.inverse().expect("camera transform must be invertible");
+        self.transform = m;
+    }
+
+    /// The ray from the camera through the center of pixel (`px`, `py`).
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        self.ray_for_position(px as f64 + 0.5, py as f64 + 0.5)
+    }
+
+    /// The ray from the camera through a position on the canvas, measured in
+    /// pixels from its top left corner.
+    pub fn ray_for_position(&self, x: f64, y: f64) -> Ray {
+        let xoffset = x * self.pixel_size;
+        let yoffset = y * self.pixel_size;
+
+        // the camera looks toward -z, so +x is to the *left*
+        let world_x = self.half_width - xoffset;
+        let world_y = self.half_height - yoffset;
+
+        let pixel = &self.inverse * Tuple::point(world_x, world_y, -1.0);
+        let origin = &self.inverse * Tuple::point(0.0, 0.0, 0.0);
+        let direction = (pixel - origin).normalize();
+        Ray::ray(origin, direction)
+    }
+
+    /// The color of pixel (`x`, `y`), supersampled as set by `antialiasing`.
+    /// Jittered lights are sampled with the pixel's own sequence.
+    pub fn pixel_color(&self, world: &World, x: usize, y: usize) -> Color {
+        let aa = &self.antialiasing;
+        let offsets = aa.offsets(x, y, self.hsize);
+        let mut sampler = aa.light_sampler(x, y, self.hsize);
+        let colors: Vec<Color> = offsets
+            .iter()
+            .map(|(dx, dy)| {
+                let ray = self.ray_for_position(x as f64 + 0.5 + dx, y as f64 + 0.5 + dy);
+                world.color_at(&ray, world.max_depth, &mut sampler)
+            })
+            .collect();
+        aa.reconstruct(&offsets, &colors)
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        let threshold = match self.antialiasing.adaptive_threshold {
+            Some(threshold) if self.antialiasing.samples > 1 => threshold,
+            _ => return self.render_tiles(|x, y| self.pixel_color(world, x, y)),
+        };
+
+        // a quick pass with one ray per pixel finds the edges worth refining
+        let plain = self.render_tiles(|x, y| {
+            let mut sampler = self.antialiasing.light_sampler(x, y, self.hsize);
+            world.color_at(&self.ray_for_pixel(x, y), world.max_depth, &mut sampler)
+        });
+        self.render_tiles(|x, y| {
+            if self.is_edge(&plain, x, y, threshold) {
+                self.pixel_color(world, x, y)
+            } else {
+                plain.pixel_at(x, y)
+            }
+        })
+    }
+
+    /// How many threads `render` will use.
+    pub fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        }
+    }
+
+    /// The tiles covering the canvas, in rows from the top left, as
+    /// (x, y, width, height).
+    pub fn tiles(&self) -> Vec<(usize, usize, usize, usize)> {
+        let size = self.tile_size.max(1);
+        let mut tiles = Vec::new();
+        for y in (0..self.vsize).step_by(size) {
+            for x in (0..self.hsize).step_by(size) {
+                tiles.push((x, y, size.min(self.hsize - x), size.min(self.vsize - y)));
+            }
+        }
+        tiles
+    }
+
+    // Colors every pixel with `shade`. Threads take tiles from a shared
+    // counter until none are left, then the tiles are copied into the canvas.
+    fn render_tiles<F>(&self, shade: F) -> Canvas
+    where
+        F: Fn(usize, usize) -> Color + Sync,
+    {
+        let tiles = self.tiles();
+        let next = AtomicUsize::new(0);
+        let threads = self.thread_count().clamp(1, tiles.len().max(1));
+
+        let (tiles_ref, next, shade) = (&tiles, &next, &shade);
+        let rendered: Vec<Vec<(usize, Vec<Color>)>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut done = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(&(x0, y0, w, h)) = tiles_ref.get(i) else {
+                                break;
+                            };
+                            let colors = (y0..y0 + h)
+                                .flat_map(|y| (x0..x0 + w).map(move |x| (x, y)))
+                                .map(|(x, y)| shade(x, y))
+                                .collect();
+                            done.push((i, colors));
+                        }
+                        done
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|w| w.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        });
+
+        let mut image = Canvas::canvas(self.hsize, self.vsize);
+        for (i, colors) in rendered.into_iter().flatten() {
+            let (x0, y0, w, _) = tiles[i];
+            for (k, c) in colors.into_iter().enumerate() {
+                image.write_pixel(x0 + k % w, y0 + k / w, c);
+            }
+        }
+        image
+    }
+
+    // whether the pixel's color differs from any of its neighbours' by more
+    // than `threshold`
+    fn is_edge(&self, image: &Canvas, x: usize, y: usize, threshold: f64) -> bool {
+        let c = image.pixel_at(x, y);
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        neighbours
+            .iter()
+            .filter(|(nx, ny)| *nx < self.hsize && *ny < self.vsize)
+            .any(|(nx, ny)| color_difference(c, image.pixel_at(*nx, *ny)) > threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::antialiasing::Filter;
+    use crate::lights::AreaLight;
+    use crate::planes::Plane;
+    use crate::shapes::Shape;
+    use crate::spheres::Sphere;
+    use crate::transformations::{rotation_y, translation, view_transform};
+    use crate::tuples::equal;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    #[test]
+    fn test_fn() {
+        let c = Camera::camera(160, 120, PI / 2.0);
+        assert_eq!(c.hsize(), 160);
+        assert_eq!(c.vsize(), 120);
+        assert!(c.field_of_view() == PI / 2.0);
+        assert_eq!(*c.transform(), Matrix::identity());
+    }
+
+    #[test]
+    fn test_pixel_size() {
+        assert!(equal(Camera::camera(200, 125, PI / 2.0).pixel_size(), 0.01));
+        assert!(equal(Camera::camera(125, 200, PI / 2.0).pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn test_setters_update_view() {
+        let mut c = Camera::camera(100, 50, PI / 3.0);
+        c.set_size(200, 125);
+        c.set_field_of_view(PI / 2.0);
+        assert!(equal(c.pixel_size(), 0.01));
+        let r = c.ray_for_pixel(0, 0);
+        assert!(r.direction.approx_eq(&Camera::camera(200, 125, PI / 2.0).ray_for_pixel(0, 0).direction));
+    }
+
+    #[test]
+    fn test_ray_for_pixel() {
+        let c = Camera::camera(201, 101, PI / 2.0);
+        let r = c.ray_for_pixel(100, 50);
+        assert!(r.origin.approx_eq(&Tuple::point(0.0, 0.0, 0.0)));
+        assert!(r.direction.approx_eq(&Tuple::vector(0.0, 0.0, -1.0)));
+
+        let r = c.ray_for_pixel(0, 0);
+        assert!(r.origin.approx_eq(&Tuple::point(0.0, 0.0, 0.0)));
+        assert!(r.direction.approx_eq(&Tuple::vector(0.66519, 0.33259, -0.66851)));
+    }
+
+    #[test]
+    fn test_ray_for_pixel_transformed() {
+        let mut c = Camera::camera(201, 101, PI / 2.0);
+        c.set_transform(&rotation_y(PI / 4.0) * &translation(0.0, -2.0, 5.0));
+        let r = c.ray_for_pixel(100, 50);
+        assert!(r.origin.approx_eq(&Tuple::point(0.0, 2.0, -5.0)));
+        assert!(r.direction.approx_eq(&Tuple::vector(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2)));
+    }
+
+    #[test]
+    fn test_render() {
+        let w = World::default_world();
+        let mut c = Camera::camera(11, 11, PI / 2.0);
+        c.set_transform(view_transform(
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        ));
+        let image = c.render(&w);
+        assert!(image.pixel_at(5, 5).approx_eq(&Color::color(0.38066, 0.47583, 0.2855)));
+    }
+
+    // a flat white disc on black: every ray sees either 0 or 1
+    fn disc() -> (World, Camera) {
+        let mut w = World::default_world();
+        w.objects.clear();
+        let mut s = Sphere::sphere();
+        s.material_mut().ambient = 1.0;
+        s.material_mut().diffuse = 0.0;
+        s.material_mut().specular = 0.0;
+        w.objects.push(Box::new(s));
+        // the disc's radius is about 8 pixels, well beyond any filter's reach
+        let mut c = Camera::camera(21, 21, PI / 6.0);
+        c.set_transform(view_transform(
+            Tuple::point(0.0, 0.0, -5.0),
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        ));
+        (w, c)
+    }
+
+    // a sphere on a floor, lit by a jittered area light above it
+    fn soft_shadow() -> (World, Camera) {
+        let mut w = World::world();
+        let mut floor = Plane::plane();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        w.objects.push(Box::new(floor));
+        w.objects.push(Box::new(Sphere::sphere()));
+        let mut light = AreaLight::area_light(
+            Tuple::point(-1.0, 3.0, -1.0),
+            Tuple::vector(2.0, 0.0, 0.0),
+            3,
+            Tuple::vector(0.0, 0.0, 2.0),
+            3,
+            Color::color(1.0, 1.0, 1.0),
+        );
+        light.jitter = true;
+        w.lights.push(light.into());
+        let mut c = Camera::camera(16, 16, PI / 3.0);
+        c.set_transform(view_transform(
+            Tuple::point(0.0, 3.0, -4.0),
+            Tuple::point(0.0, -1.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        ));
+        (w, c)
+    }
+
+    fn is_blend(c: Color) -> bool {
+        c.red > 0.0 && c.red < 1.0
+    }
+
+    #[test]
+    fn test_ray_for_position() {
+        let c = Camera::camera(201, 101, PI / 2.0);
+        let r = c.ray_for_position(100.5, 50.5);
+        assert!(r.direction.approx_eq(&Tuple::vector(0.0, 0.0, -1.0)));
+        assert_eq!(c.ray_for_position(0.5, 0.5), c.ray_for_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_render_supersampled() {
+        let (w, mut c) = disc();
+        let plain = c.render(&w);
+        let all = (0..21).flat_map(|y| (0..21).map(move |x| (x, y)));
+        assert!(!all.clone().any(|(x, y)| is_blend(plain.pixel_at(x, y))));
+
+        for filter in [Filter::Box, Filter::Tent, Filter::Gaussian, Filter::Mitchell] {
+            c.antialiasing = Antialiasing {
+                filter,
+                ..Antialiasing::supersampled(4)
+            };
+            let image = c.render(&w);
+            // edges are smoothed, the middle and the corners are not
+            assert!(all.clone().any(|(x, y)| is_blend(image.pixel_at(x, y))), "{:?}", filter);
+            assert!(image.pixel_at(10, 10).approx_eq(&Color::color(1.0, 1.0, 1.0)));
+            assert!(image.pixel_at(0, 0).approx_eq(&Color::color(0.0, 0.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn test_render_adaptive() {
+        let (w, mut c) = disc();
+        let plain = c.render(&w);
+        c.antialiasing = Antialiasing::supersampled(4);
+        let full = c.render(&w);
+        c.antialiasing.adaptive_threshold = Some(0.1);
+        let adaptive = c.render(&w);
+
+        // only pixels on the disc's edge are refined
+        let mut refined = 0;
+        for y in 0..21 {
+            for x in 0..21 {
+                let a = adaptive.pixel_at(x, y);
+                if c.is_edge(&plain, x, y, 0.1) {
+                    assert_eq!(a, full.pixel_at(x, y));
+                    refined += 1;
+                } else {
+                    assert_eq!(a, plain.pixel_at(x, y));
+                }
+            }
+        }
+        assert!(refined > 0 && refined < 21 * 21);
+    }
+
+    #[test]
+    fn test_scene_is_shareable() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<World>();
+        assert_send_sync::<Camera>();
+    }
+
+    #[test]
+    fn test_tiles() {
+        let mut c = Camera::camera(40, 20, PI / 2.0);
+        c.tile_size = 16;
+        assert_eq!(
+            c.tiles(),
+            vec![(0, 0, 16, 16), (16, 0, 16, 16), (32, 0, 8, 16), (0, 16, 16, 4), (16, 16, 16, 4), (32, 16, 8, 4)]
+        );
+        // every pixel is covered exactly once
+        let covered: usize = c.tiles().iter().map(|(_, _, w, h)| w * h).sum();
+        assert_eq!(covered, 40 * 20);
+    }
+
+    #[test]
+    fn test_thread_count() {
+        let mut c = Camera::camera(10, 10, PI / 2.0);
+        assert_eq!(c.threads, 0);
+        assert!(c.thread_count() >= 1);
+        c.threads = 3;
+        assert_eq!(c.thread_count(), 3);
+    }
+
+    #[test]
+    fn test_render_threads_deterministic() {
+        let (w, mut c) = disc();
+        c.antialiasing = Antialiasing {
+            adaptive_threshold: Some(0.1),
+            ..Antialiasing::supersampled(3)
+        };
+        let (soft, mut soft_camera) = soft_shadow();
+        soft_camera.antialiasing = Antialiasing::supersampled(2);
+
+        for (w, mut c) in [(w, c), (soft, soft_camera)] {
+            c.threads = 1;
+            c.tile_size = 64;
+            let expected = c.render(&w);
+            for (threads, tile_size) in [(1, 4), (2, 4), (4, 5), (8, 1), (3, 100)] {
+                c.threads = threads;
+                c.tile_size = tile_size;
+                let image = c.render(&w);
+                assert_eq!(image.data, expected.data, "{} threads, {} pixel tiles", threads, tile_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_jittered_light() {
+        // the penumbra really is jittered: another seed gives other samples
+        let (w, mut c) = soft_shadow();
+        let first = c.render(&w);
+        c.antialiasing.seed = 1;
+        assert_ne!(c.render(&w).data, first.data);
+    }
+}
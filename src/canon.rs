@@ -1,43 +1,71 @@
+use std::f64::consts::PI;
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{self, BufWriter};
 
-use crate::tuples::Tuple;
-use crate::canvas::Canvas;
-use crate::colors::Color;
+use ray::transformations::{scaling, translation, view_transform};
+use ray::{Camera, Color, PointLight, PpmFormat, Quantizer, Shape, Sphere, Tuple, World};
 
-#[derive(Debug)]
-pub struct Proj {
-    pub p: Tuple,
-    pub v: Tuple
-}
+/// Three spheres sitting on a floor, lit from the upper left.
+pub fn scene() -> World {
+    let mut floor = Sphere::sphere();
+    floor.set_transform(scaling(10.0, 0.01, 10.0));
+    floor.material_mut().color = Color::color(1.0, 0.9, 0.9);
+    floor.material_mut().specular = 0.0;
+
+    let mut middle = Sphere::sphere();
+    middle.set_transform(translation(-0.5, 1.0, 0.5));
+    middle.material_mut().color = Color::color(0.1, 1.0, 0.5);
+    middle.material_mut().diffuse = 0.7;
+    middle.material_mut().specular = 0.3;
+
+    let mut right = Sphere::sphere();
+    right.set_transform(&translation(1.5, 0.5, -0.5) * &scaling(0.5, 0.5, 0.5));
+    right.material_mut().color = Color::color(0.5, 1.0, 0.1);
+    right.material_mut().diffuse = 0.7;
+    right.material_mut().specular = 0.3;
 
-pub struct Env {
-    pub g: Tuple,
-    pub w: Tuple
+    let mut left = Sphere::sphere();
+    left.set_transform(&translation(-1.5, 0.33, -0.75) * &scaling(0.33, 0.33, 0.33));
+    left.material_mut().color = Color::color(1.0, 0.8, 0.1);
+    left.material_mut().diffuse = 0.7;
+    left.material_mut().specular = 0.3;
+
+    let mut w = World::world();
+    w.objects = vec![
+        Box::new(floor),
+        Box::new(middle),
+        Box::new(right),
+        Box::new(left),
+    ];
+    w.lights = vec![PointLight::point_light(
+        Tuple::point(-10.0, 10.0, -10.0),
+        Color::color(1.0, 1.0, 1.0),
+    )
+    .into()];
+    w
 }
 
-pub fn tick(e: &Env, p: &Proj) -> Proj {
-    let pos = p.p + p.v;
-    let vel = p.v + e.g + e.w;
-    Proj{p: pos, v: vel}
+/// Renders the scene with `threads` threads (0 for all cores) to `output`.
+pub fn runme(threads: usize, output: &str) -> io::Result<()> {
+    let mut camera = Camera::camera(900, 550, PI / 3.0);
+    camera.set_transform(view_transform(
+        Tuple::point(0.0, 1.5, -5.0),
+        Tuple::point(0.0, 1.0, 0.0),
+        Tuple::vector(0.0, 1.0, 0.0),
+    ));
+    render(camera, &scene(), threads, output)
 }
 
-pub fn runme() {
-    let e = Env{g: Tuple::vector(0.0, -0.1, 0.0), w: Tuple::vector(-0.03, 0.0, 0.0)};
-    let mut p = Proj{p: Tuple::point(0.0, 1.0, 1.0), v: Tuple::vector(1.0, 1.8, 0.0).normalize() * 11.25};
-    
-    let mut c = Canvas::canvas(900, 550);
-    let red = Color::color(1.0, 0.0, 0.0);
-    
-    while p.p.y > 0.0 {
-        let x = p.p.x as usize;
-        let y = (550.0 - p.p.y) as usize;
-        c.write_pixel(x, y, red);
-        p = tick(&e, &p);
-        
+/// Renders `world` with `threads` threads (0 for all cores) to `output`, as a
+/// PPM file if its name ends in `.ppm` and a PNG otherwise.
+pub fn render(mut camera: Camera, world: &World, threads: usize, output: &str) -> io::Result<()> {
+    camera.threads = threads;
+
+    let c = camera.render(world);
+    let file = BufWriter::new(File::create(output)?);
+    if output.ends_with(".ppm") {
+        c.write_ppm(file, PpmFormat::P6, &Quantizer::quantizer())
+    } else {
+        c.write_png(file, &Quantizer::quantizer())
     }
-    let ppm = c.to_ppm();
-    let mut file = File::create("foo.ppm").unwrap();
-    file.write_all(ppm.as_bytes()).unwrap();
-    
-}
\ No newline at end of file
+}
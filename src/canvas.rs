@@ -1,6 +1,6 @@
 use ndarray::{Array2, ArrayBase, Dim, OwnedRepr};
 
-use crate::colors::Color;
+use crate::colors::{Color, Quantizer};
 
 #[derive(Debug, Clone)]
 pub struct Canvas {
@@ -29,6 +29,7 @@ impl Canvas {
     pub fn to_ppm(&self) -> String {
         let mut s = String::new();
         let mut line_len: u32 = 0;
+        let quantizer = Quantizer::quantizer();
         s.push_str("P3\n");
         s.push_str(&format!("{} {}\n", self.width, self.height));
         s.push_str("255\n");
@@ -36,10 +37,7 @@ impl Canvas {
             
             for x in 0..self.width {
                 
-                let c = self.pixel_at(x, y);
-                let r = (c.red * 256.0) as u8;
-                let g = (c.green * 256.0) as u8;
-                let b = (c.blue * 256.0) as u8;
+                let [r, g, b] = self.pixel_at(x, y).quantize(&quantizer, x, y);
                 s = fix_line(s, r, &mut line_len);
                 s = fix_line(s, g, &mut line_len);
                 s = fix_line(s, b, &mut line_len);
@@ -57,7 +55,7 @@ impl Canvas {
     }
 }
 
-fn fix_line(mut l: String, v: u8,  line_len: &mut u32 ) -> String {
+fn fix_line(mut l: String, v: u16,  line_len: &mut u32 ) -> String {
     let s = &format!("{}", v);
     l.push_str(s);
     *line_len = *line_len + 1 + s.len() as u32;
@@ -113,5 +111,4 @@ mod tests {
         
         assert_eq!(l.trim(), "1 2 3 4");
     }
-
 }
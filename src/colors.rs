@@ -3,6 +3,8 @@ use cucumber::Parameter;
 use std::cmp::Eq;
 use std::ops::{Add, Mul, Sub};
 
+use crate::tuples::equal;
+
 #[derive(Debug, Default, Clone, Copy, Parameter)]
 #[param(name = "color", regex = r"color\(.+, .+, .+\)")]
 pub struct Color {
@@ -19,6 +21,76 @@ impl Color {
             blue: b,
         }
     }
+
+    /// Like `==` but tolerates rounding errors up to `EPSILON`.
+    pub fn approx_eq(&self, other: &Color) -> bool {
+        equal(self.red, other.red) && equal(self.green, other.green) && equal(self.blue, other.blue)
+    }
+}
+
+/// How linear color values are encoded before quantization.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Linear,
+    /// The sRGB transfer curve, what most viewers assume for 8 bit images.
+    Srgb,
+    /// A plain power curve, `v^(1/gamma)`.
+    Gamma(f64),
+}
+
+impl Encoding {
+    pub fn encode(&self, v: f64) -> f64 {
+        match self {
+            Encoding::Linear => v,
+            Encoding::Srgb if v <= 0.0031308 => 12.92 * v,
+            Encoding::Srgb => 1.055 * v.powf(1.0 / 2.4) - 0.055,
+            Encoding::Gamma(g) => v.powf(1.0 / g),
+        }
+    }
+}
+
+// 4x4 Bayer matrix for ordered dithering
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Turns color channels into integers from 0 to `max_value`: values are
+/// clamped to 0..1, encoded, then rounded to the nearest step, with an
+/// optional ordered dither to break up banding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantizer {
+    pub max_value: u16,
+    pub encoding: Encoding,
+    pub dither: bool,
+}
+
+impl Quantizer {
+    /// Linear 0..255 without dithering, as the book's PPM output expects.
+    pub fn quantizer() -> Quantizer {
+        Quantizer {
+            max_value: 255,
+            encoding: Encoding::Linear,
+            dither: false,
+        }
+    }
+
+    /// Quantizes one channel of the pixel at (`x`, `y`); the position only
+    /// matters when dithering.
+    pub fn quantize(&self, v: f64, x: usize, y: usize) -> u16 {
+        // clamp lets NaN through
+        let v = if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) };
+        let max = self.max_value as f64;
+        let mut scaled = self.encoding.encode(v) * max;
+        if self.dither {
+            // an offset in -0.5..0.5 of a step
+            scaled += (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0 - 0.5;
+        }
+        scaled.round().clamp(0.0, max) as u16
+    }
+}
+
+impl Color {
+    pub fn quantize(&self, q: &Quantizer, x: usize, y: usize) -> [u16; 3] {
+        [q.quantize(self.red, x, y), q.quantize(self.green, x, y), q.quantize(self.blue, x, y)]
+    }
 }
 
 impl FromStr for Color {
@@ -160,4 +232,64 @@ mod tests {
         assert!(t3.green == 0.2);
         assert!(t3.blue == 0.2);
     }
+
+    #[test]
+    fn test_quantize() {
+        let q = Quantizer::quantizer();
+        let cases = [(0.0, 0), (1.0, 255), (0.5, 128), (0.499, 127), (0.999, 255), (1.5, 255), (-0.5, 0), (f64::NAN, 0)];
+        for (v, expected) in cases {
+            assert_eq!(q.quantize(v, 0, 0), expected, "{}", v);
+        }
+
+        let q = Quantizer {
+            max_value: 65535,
+            ..Quantizer::quantizer()
+        };
+        assert_eq!(Color::color(1.0, 0.5, 0.0).quantize(&q, 0, 0), [65535, 32768, 0]);
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(Encoding::Linear.encode(0.2), 0.2);
+        assert!(equal(Encoding::Srgb.encode(0.001), 0.01292));
+        assert!(equal(Encoding::Srgb.encode(0.5), 0.73536));
+        assert!(equal(Encoding::Srgb.encode(1.0), 1.0));
+        assert!(equal(Encoding::Gamma(2.0).encode(0.25), 0.5));
+
+        let q = Quantizer {
+            encoding: Encoding::Srgb,
+            ..Quantizer::quantizer()
+        };
+        assert_eq!(q.quantize(0.5, 0, 0), 188);
+        assert_eq!(q.quantize(0.0, 0, 0), 0);
+        assert_eq!(q.quantize(1.0, 0, 0), 255);
+    }
+
+    #[test]
+    fn test_dither() {
+        let q = Quantizer {
+            dither: true,
+            ..Quantizer::quantizer()
+        };
+        // a value between two steps dithers to a mix of both, in proportion
+        let v = 100.25 / 255.0;
+        let mut total = 0;
+        for y in 0..4 {
+            for x in 0..4 {
+                let n = q.quantize(v, x, y);
+                assert!(n == 100 || n == 101);
+                total += n as u32;
+            }
+        }
+        assert_eq!(total, 16 * 100 + 4);
+
+        // exact steps and the ends of the range are left alone
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(q.quantize(100.0 / 255.0, x, y), 100);
+                assert_eq!(q.quantize(0.0, x, y), 0);
+                assert_eq!(q.quantize(1.0, x, y), 255);
+            }
+        }
+    }
 }
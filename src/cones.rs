@@ -0,0 +1,247 @@
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::rays::Ray;
+use crate::shapes::{Shape, ShapeProps};
+use crate::tuples::{equal, Tuple, EPSILON};
+
+/// A double-napped cone around the y axis with its apex at the origin,
+/// optionally truncated to `minimum..maximum` and capped when `closed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cone {
+    pub props: ShapeProps,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    pub fn cone() -> Cone {
+        Cone {
+            props: ShapeProps::shape_props(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    // the radius of a cone's cap equals the absolute y of the plane it sits on
+    fn check_cap(ray: &Ray, t: f64, y: f64) -> bool {
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+        x * x + z * z <= y * y
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        // an unbounded end has no cap, however wide the cone gets there
+        for y in [self.minimum, self.maximum].into_iter().filter(|y| y.is_finite()) {
+            let t = (y - ray.origin.y) / ray.direction.y;
+            if Cone::check_cap(ray, t, y) {
+                xs.push(Intersection::intersection(t, self));
+            }
+        }
+    }
+
+    fn push_if_in_bounds<'a>(&'a self, ray: &Ray, t: f64, xs: &mut Vec<Intersection<'a>>) {
+        let y = ray.origin.y + t * ray.direction.y;
+        if self.minimum < y && y < self.maximum {
+            xs.push(Intersection::intersection(t, self));
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Cone {
+        Cone::cone()
+    }
+}
+
+impl Shape for Cone {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut xs = Vec::new();
+        let (o, d) = (ray.origin, ray.direction);
+
+        let a = d.x.powi(2) - d.y.powi(2) + d.z.powi(2);
+        let b = 2.0 * o.x * d.x - 2.0 * o.y * d.y + 2.0 * o.z * d.z;
+        let c = o.x.powi(2) - o.y.powi(2) + o.z.powi(2);
+
+        if equal(a, 0.0) {
+            if !equal(b, 0.0) {
+                // the ray is parallel to one of the cone's halves and hits the other once
+                self.push_if_in_bounds(ray, -c / (2.0 * b), &mut xs);
+            } else if equal(c, 0.0) {
+                // the ray lies on the cone's surface, which only ever happens
+                // along a line through the apex; count that as the one hit
+                self.push_if_in_bounds(ray, -o.y / d.y, &mut xs);
+            }
+        } else {
+            let disc = b * b - 4.0 * a * c;
+            // tiny negative values come from rays through the apex
+            if disc >= -EPSILON {
+                let disc = disc.max(0.0);
+                let t0 = (-b - disc.sqrt()) / (2.0 * a);
+                let t1 = (-b + disc.sqrt()) / (2.0 * a);
+                self.push_if_in_bounds(ray, t0, &mut xs);
+                self.push_if_in_bounds(ray, t1, &mut xs);
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        Intersections::intersections(xs)
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let dist = point.x.powi(2) + point.z.powi(2);
+
+        if dist < self.maximum.powi(2) && point.y >= self.maximum - EPSILON {
+            Tuple::vector(0.0, 1.0, 0.0)
+        } else if dist < self.minimum.powi(2) && point.y <= self.minimum + EPSILON {
+            Tuple::vector(0.0, -1.0, 0.0)
+        } else {
+            let mut y = dist.sqrt();
+            if point.y > 0.0 {
+                y = -y;
+            }
+            Tuple::vector(point.x, y, point.z)
+        }
+    }
+
+    /// The cone is widest at whichever end is furthest from its apex.
+    fn bounds(&self) -> BoundingBox {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+        BoundingBox::bounding_box(
+            Tuple::point(-limit, self.minimum, -limit),
+            Tuple::point(limit, self.maximum, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ray(o: (f64, f64, f64), d: (f64, f64, f64)) -> Ray {
+        Ray::ray(Tuple::point(o.0, o.1, o.2), Tuple::vector(d.0, d.1, d.2).normalize())
+    }
+
+    #[test]
+    fn test_intersect() {
+        let c = Cone::cone();
+        for (o, d, t0, t1) in [
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 5.0, 5.0),
+            ((0.0, 0.0, -5.0), (1.0, 1.0, 1.0), 8.66025, 8.66025),
+            ((1.0, 1.0, -5.0), (-0.5, -1.0, 1.0), 4.55006, 49.44994),
+        ] {
+            let xs = c.local_intersect(&ray(o, d));
+            assert_eq!(xs.len(), 2);
+            assert!((xs[0].t - t0).abs() < 0.0001);
+            assert!((xs[1].t - t1).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_intersect_parallel_to_half() {
+        let c = Cone::cone();
+        let xs = c.local_intersect(&ray((0.0, 0.0, -1.0), (0.0, 1.0, 1.0)));
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0].t - 0.35355).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_intersect_through_apex() {
+        // a ray along the cone's surface is reported as hitting the apex
+        let c = Cone::cone();
+        let xs = c.local_intersect(&ray((-1.0, -1.0, 0.0), (1.0, 1.0, 0.0)));
+        assert_eq!(xs.len(), 1);
+        assert!(equal(xs[0].t, 2.0_f64.sqrt()));
+
+        let xs = c.local_intersect(&ray((0.0, 2.0, -2.0), (0.0, -1.0, 1.0)));
+        assert_eq!(xs.len(), 1);
+        assert!(equal(xs[0].t, 8.0_f64.sqrt()));
+
+        let xs = c.local_intersect(&ray((0.0, -1.0, 0.0), (0.0, 1.0, 0.0)));
+        assert_eq!(xs.len(), 2);
+        assert!(equal(xs[0].t, 1.0));
+        assert!(equal(xs[1].t, 1.0));
+    }
+
+    #[test]
+    fn test_intersect_caps() {
+        let mut c = Cone::cone();
+        c.minimum = -0.5;
+        c.maximum = 0.5;
+        c.closed = true;
+        for (o, d, count) in [
+            ((0.0, 0.0, -5.0), (0.0, 1.0, 0.0), 0),
+            ((0.0, 0.0, -0.25), (0.0, 1.0, 1.0), 2),
+            ((0.0, 0.0, -0.25), (0.0, 1.0, 0.0), 4),
+        ] {
+            assert_eq!(c.local_intersect(&ray(o, d)).len(), count);
+        }
+    }
+
+    #[test]
+    fn test_intersect_caps_unbounded() {
+        let mut c = Cone::cone();
+        c.closed = true;
+        let xs = c.local_intersect(&ray((0.0, 0.0, -5.0), (1.0, 1.0, 1.0)));
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t - 8.66025).abs() < 0.0001);
+        assert!((xs[1].t - 8.66025).abs() < 0.0001);
+        assert_eq!(c.local_intersect(&ray((0.0, 0.5, -5.0), (1.0, 0.1, 0.0))).len(), 0);
+
+        // a finite end is still capped: the apex twice, then the top
+        c.maximum = 1.0;
+        let xs = c.local_intersect(&ray((0.0, 0.5, 0.0), (0.0, 1.0, 0.0)));
+        assert_eq!(xs.len(), 3);
+        assert!(equal(xs[2].t, 0.5));
+    }
+
+    #[test]
+    fn test_normal() {
+        let c = Cone::cone();
+        let sqrt2 = 2.0_f64.sqrt();
+        for (p, n) in [
+            ((0.0, 0.0, 0.0), (0.0, 0.0, 0.0)),
+            ((1.0, 1.0, 1.0), (1.0, -sqrt2, 1.0)),
+            ((-1.0, -1.0, 0.0), (-1.0, 1.0, 0.0)),
+        ] {
+            let normal = c.local_normal_at(Tuple::point(p.0, p.1, p.2));
+            assert!(normal.approx_eq(&Tuple::vector(n.0, n.1, n.2)));
+        }
+    }
+
+    #[test]
+    fn test_normal_caps() {
+        let mut c = Cone::cone();
+        c.minimum = -1.0;
+        c.maximum = 1.0;
+        c.closed = true;
+        assert_eq!(c.local_normal_at(Tuple::point(0.5, 1.0, 0.0)), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(c.local_normal_at(Tuple::point(0.0, -1.0, 0.5)), Tuple::vector(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = Cone::cone().bounds();
+        assert!(b.min.x == f64::NEG_INFINITY && b.min.y == f64::NEG_INFINITY);
+        assert!(b.max.x == f64::INFINITY && b.max.y == f64::INFINITY);
+
+        let mut cone = Cone::cone();
+        cone.minimum = -5.0;
+        cone.maximum = 3.0;
+        let b = cone.bounds();
+        assert_eq!(b.min, Tuple::point(-5.0, -5.0, -5.0));
+        assert_eq!(b.max, Tuple::point(5.0, 3.0, 5.0));
+    }
+}
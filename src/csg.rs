@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use crate::bounds::BoundingBox;
+use crate::intersections::Intersections;
+use crate::matrices::Matrix;
+use crate::rays::Ray;
+use crate::shapes::{Parent, Shape, ShapeProps};
+use crate::tuples::Tuple;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    /// Whether a hit on one operand survives the operation. `lhit` tells which
+    /// operand was hit, `inl` and `inr` whether the ray is currently inside
+    /// the left and right operands.
+    pub fn intersection_allowed(&self, lhit: bool, inl: bool, inr: bool) -> bool {
+        match self {
+            CsgOperation::Union => (lhit && !inr) || (!lhit && !inl),
+            CsgOperation::Intersection => (lhit && inr) || (!lhit && inl),
+            CsgOperation::Difference => (lhit && !inr) || (!lhit && inl),
+        }
+    }
+}
+
+/// Constructive solid geometry: two shapes combined by a boolean operation.
+///
+/// Like a group, a CSG shape passes its transform down to its operands, which
+/// may themselves be groups or other CSG shapes.
+#[derive(Debug)]
+pub struct Csg {
+    props: ShapeProps,
+    pub operation: CsgOperation,
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+    // the link handed to both operands, kept in sync with `props`
+    link: Arc<Parent>,
+    bounds: BoundingBox,
+}
+
+impl Csg {
+    pub fn csg(operation: CsgOperation, left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        let props = ShapeProps::shape_props();
+        let mut bounds = left.parent_space_bounds();
+        bounds.merge(&right.parent_space_bounds());
+        let mut csg = Csg {
+            link: Arc::new(Parent::parent(&props)),
+            props,
+            operation,
+            left,
+            right,
+            bounds,
+        };
+        csg.update_children();
+        csg
+    }
+
+    pub fn union(left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg::csg(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg::csg(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Box<dyn Shape>, right: Box<dyn Shape>) -> Csg {
+        Csg::csg(CsgOperation::Difference, left, right)
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.left.as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.right.as_ref()
+    }
+
+    /// Keeps the intersections that lie on the surface of the combined shape.
+    /// `xs` must be sorted, as `Intersections` always are.
+    pub fn filter_intersections<'a>(&self, xs: Intersections<'a>) -> Intersections<'a> {
+        let mut inl = false;
+        let mut inr = false;
+        let mut list = Vec::new();
+
+        for i in xs.list {
+            let lhit = self.left.includes(i.object);
+            if self.operation.intersection_allowed(lhit, inl, inr) {
+                list.push(i);
+            }
+            if lhit {
+                inl = !inl;
+            } else {
+                inr = !inr;
+            }
+        }
+        Intersections { list }
+    }
+
+    fn update_children(&mut self) {
+        self.link = Arc::new(Parent::parent(&self.props));
+        self.left.set_parent(Some(self.link.clone()));
+        self.right.set_parent(Some(self.link.clone()));
+    }
+}
+
+impl Shape for Csg {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn set_transform(&mut self, m: Matrix) {
+        self.props.set_transform(m);
+        self.update_children();
+    }
+
+    fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.props.set_parent(parent);
+        self.update_children();
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        if !self.bounds.intersects(ray) {
+            return Intersections::default();
+        }
+        let mut xs = self.left.intersect(ray);
+        xs.extend(self.right.intersect(ray));
+        self.filter_intersections(xs)
+    }
+
+    /// Intersections always point at one of the operands, never at the CSG shape.
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        panic!("CSG shapes have no normals of their own")
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        self.left.divide(threshold);
+        self.right.divide(threshold);
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.left.includes(other) || self.right.includes(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cubes::Cube;
+    use crate::groups::Group;
+    use crate::intersections::Intersection;
+    use crate::spheres::Sphere;
+    use crate::transformations::{scaling, translation};
+
+    #[test]
+    fn test_fn() {
+        let c = Csg::union(Box::new(Sphere::sphere()), Box::new(Cube::cube()));
+        assert_eq!(c.operation, CsgOperation::Union);
+        assert!(c.left().props().parent().is_some());
+        assert!(c.right().props().parent().is_some());
+    }
+
+    #[test]
+    fn test_intersection_allowed() {
+        use CsgOperation::*;
+        #[rustfmt::skip]
+        let cases = [
+            (Union, true, true, true, false),
+            (Union, true, true, false, true),
+            (Union, true, false, true, false),
+            (Union, true, false, false, true),
+            (Union, false, true, true, false),
+            (Union, false, true, false, false),
+            (Union, false, false, true, true),
+            (Union, false, false, false, true),
+            (Intersection, true, true, true, true),
+            (Intersection, true, true, false, false),
+            (Intersection, true, false, true, true),
+            (Intersection, true, false, false, false),
+            (Intersection, false, true, true, true),
+            (Intersection, false, true, false, true),
+            (Intersection, false, false, true, false),
+            (Intersection, false, false, false, false),
+            (Difference, true, true, true, false),
+            (Difference, true, true, false, true),
+            (Difference, true, false, true, false),
+            (Difference, true, false, false, true),
+            (Difference, false, true, true, true),
+            (Difference, false, true, false, true),
+            (Difference, false, false, true, false),
+            (Difference, false, false, false, false),
+        ];
+        for (op, lhit, inl, inr, expected) in cases {
+            assert_eq!(op.intersection_allowed(lhit, inl, inr), expected, "{:?} {} {} {}", op, lhit, inl, inr);
+        }
+    }
+
+    #[test]
+    fn test_filter_intersections() {
+        let cases = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+        for (op, x0, x1) in cases {
+            let c = Csg::csg(op, Box::new(Sphere::sphere()), Box::new(Cube::cube()));
+            let (s1, s2) = (c.left(), c.right());
+            let xs = Intersections::intersections(vec![
+                Intersection::intersection(1.0, s1),
+                Intersection::intersection(2.0, s2),
+                Intersection::intersection(3.0, s1),
+                Intersection::intersection(4.0, s2),
+            ]);
+            let expected = [xs[x0], xs[x1]];
+            let result = c.filter_intersections(xs);
+            assert_eq!(result.list, expected);
+        }
+    }
+
+    #[test]
+    fn test_intersect_misses() {
+        let c = Csg::union(Box::new(Sphere::sphere()), Box::new(Cube::cube()));
+        let r = Ray::ray(Tuple::point(0.0, 2.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(c.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn test_intersect_hits() {
+        let mut s2 = Sphere::sphere();
+        s2.set_transform(translation(0.0, 0.0, 0.5));
+        let c = Csg::union(Box::new(Sphere::sphere()), Box::new(s2));
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == 4.0);
+        assert!(std::ptr::addr_eq(xs[0].object, c.left()));
+        assert!(xs[1].t == 6.5);
+        assert!(std::ptr::addr_eq(xs[1].object, c.right()));
+    }
+
+    #[test]
+    fn test_set_transform_moves_operands() {
+        let mut c = Csg::union(Box::new(Sphere::sphere()), Box::new(Cube::cube()));
+        c.set_transform(translation(5.0, 0.0, 0.0));
+        assert_eq!(*c.props().transform(), translation(5.0, 0.0, 0.0));
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(c.intersect(&r).is_empty());
+        let r = Ray::ray(Tuple::point(5.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = c.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        let n = xs[0].object.normal_at(r.position(xs[0].t));
+        assert!(n.approx_eq(&Tuple::vector(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn test_difference_with_group_operand() {
+        // a cube with two spheres carved out of its front and back faces
+        let mut front = Sphere::sphere();
+        front.set_transform(&translation(0.0, 0.0, -1.0) * &scaling(0.5, 0.5, 0.5));
+        let mut back = Sphere::sphere();
+        back.set_transform(&translation(0.0, 0.0, 1.0) * &scaling(0.5, 0.5, 0.5));
+        let mut holes = Group::group();
+        holes.add_child(Box::new(front));
+        holes.add_child(Box::new(back));
+        let c = Csg::difference(Box::new(Cube::cube()), Box::new(holes));
+
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let ts: Vec<f64> = c.intersect(&r).list.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![4.5, 5.5]);
+    }
+
+    #[test]
+    fn test_nested_transformed() {
+        let inner = Csg::intersection(Box::new(Sphere::sphere()), Box::new(Cube::cube()));
+        let mut outer = Csg::union(Box::new(inner), Box::new(Sphere::sphere()));
+        outer.set_transform(translation(5.0, 0.0, 0.0));
+        assert_eq!(outer.bounds().min, Tuple::point(-1.0, -1.0, -1.0));
+
+        let r = Ray::ray(Tuple::point(5.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = outer.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(outer.includes(xs[0].object));
+        // normals account for the CSG's transform
+        let n = xs[0].object.normal_at(r.position(xs[0].t));
+        assert!(n.approx_eq(&Tuple::vector(0.0, 0.0, -1.0)));
+    }
+}
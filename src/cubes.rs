@@ -0,0 +1,166 @@
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::rays::Ray;
+use crate::shapes::{Shape, ShapeProps};
+use crate::tuples::{Tuple, EPSILON};
+
+/// An axis-aligned cube spanning -1..1 on every axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cube {
+    pub props: ShapeProps,
+}
+
+impl Cube {
+    pub fn cube() -> Cube {
+        Cube {
+            props: ShapeProps::shape_props(),
+        }
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Cube {
+        Cube::cube()
+    }
+}
+
+/// Where a ray enters and leaves the slab between `min` and `max` on one axis.
+pub fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (tmin_numerator * f64::INFINITY, tmax_numerator * f64::INFINITY)
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, -1.0, 1.0);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, -1.0, 1.0);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, -1.0, 1.0);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Intersections::default();
+        }
+        Intersections::intersections(vec![
+            Intersection::intersection(tmin, self),
+            Intersection::intersection(tmax, self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let maxc = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+        if maxc == point.x.abs() {
+            Tuple::vector(point.x, 0.0, 0.0)
+        } else if maxc == point.y.abs() {
+            Tuple::vector(0.0, point.y, 0.0)
+        } else {
+            Tuple::vector(0.0, 0.0, point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::bounding_box(Tuple::point(-1.0, -1.0, -1.0), Tuple::point(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersect_hits() {
+        let c = Cube::cube();
+        let cases = [
+            ((5.0, 0.5, 0.0), (-1.0, 0.0, 0.0), 4.0, 6.0),
+            ((-5.0, 0.5, 0.0), (1.0, 0.0, 0.0), 4.0, 6.0),
+            ((0.5, 5.0, 0.0), (0.0, -1.0, 0.0), 4.0, 6.0),
+            ((0.5, -5.0, 0.0), (0.0, 1.0, 0.0), 4.0, 6.0),
+            ((0.5, 0.0, 5.0), (0.0, 0.0, -1.0), 4.0, 6.0),
+            ((0.5, 0.0, -5.0), (0.0, 0.0, 1.0), 4.0, 6.0),
+            ((0.0, 0.5, 0.0), (0.0, 0.0, 1.0), -1.0, 1.0),
+        ];
+        for ((ox, oy, oz), (dx, dy, dz), t1, t2) in cases.iter() {
+            let r = Ray::ray(Tuple::point(*ox, *oy, *oz), Tuple::vector(*dx, *dy, *dz));
+            let xs = c.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            assert!(xs[0].t == *t1);
+            assert!(xs[1].t == *t2);
+        }
+    }
+
+    #[test]
+    fn test_intersect_misses() {
+        let c = Cube::cube();
+        let cases = [
+            ((-2.0, 0.0, 0.0), (0.2673, 0.5345, 0.8018)),
+            ((0.0, -2.0, 0.0), (0.8018, 0.2673, 0.5345)),
+            ((0.0, 0.0, -2.0), (0.5345, 0.8018, 0.2673)),
+            ((2.0, 0.0, 2.0), (0.0, 0.0, -1.0)),
+            ((0.0, 2.0, 2.0), (0.0, -1.0, 0.0)),
+            ((2.0, 2.0, 0.0), (-1.0, 0.0, 0.0)),
+        ];
+        for ((ox, oy, oz), (dx, dy, dz)) in cases.iter() {
+            let r = Ray::ray(Tuple::point(*ox, *oy, *oz), Tuple::vector(*dx, *dy, *dz));
+            assert!(c.local_intersect(&r).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_intersect_grazing_edge() {
+        // a ray running exactly along an edge touches the cube at a single t
+        let c = Cube::cube();
+        let r = Ray::ray(Tuple::point(1.0, 1.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = c.local_intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == 4.0);
+        assert!(xs[1].t == 6.0);
+    }
+
+    #[test]
+    fn test_normal() {
+        let c = Cube::cube();
+        let cases = [
+            ((1.0, 0.5, -0.8), (1.0, 0.0, 0.0)),
+            ((-1.0, -0.2, 0.9), (-1.0, 0.0, 0.0)),
+            ((-0.4, 1.0, -0.1), (0.0, 1.0, 0.0)),
+            ((0.3, -1.0, -0.7), (0.0, -1.0, 0.0)),
+            ((-0.6, 0.3, 1.0), (0.0, 0.0, 1.0)),
+            ((0.4, 0.4, -1.0), (0.0, 0.0, -1.0)),
+            ((1.0, 1.0, 1.0), (1.0, 0.0, 0.0)),
+            ((-1.0, -1.0, -1.0), (-1.0, 0.0, 0.0)),
+        ];
+        for ((px, py, pz), (nx, ny, nz)) in cases.iter() {
+            let n = c.local_normal_at(Tuple::point(*px, *py, *pz));
+            assert_eq!(n, Tuple::vector(*nx, *ny, *nz));
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = Cube::cube().bounds();
+        assert_eq!(b.min, Tuple::point(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Tuple::point(1.0, 1.0, 1.0));
+    }
+}
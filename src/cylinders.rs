@@ -0,0 +1,229 @@
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::rays::Ray;
+use crate::shapes::{Shape, ShapeProps};
+use crate::tuples::{equal, Tuple, EPSILON};
+
+/// A cylinder of radius 1 around the y axis, optionally truncated to
+/// `minimum..maximum` (exclusive) and capped at both ends when `closed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    pub props: ShapeProps,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub fn cylinder() -> Cylinder {
+        Cylinder {
+            props: ShapeProps::shape_props(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    // whether the point at `t` lies within the unit radius of a cap
+    fn check_cap(ray: &Ray, t: f64) -> bool {
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+        x * x + z * z <= 1.0
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.y) / ray.direction.y;
+            if Cylinder::check_cap(ray, t) {
+                xs.push(Intersection::intersection(t, self));
+            }
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Cylinder {
+        Cylinder::cylinder()
+    }
+}
+
+impl Shape for Cylinder {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut xs = Vec::new();
+        let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+
+        // a ray parallel to the y axis can only hit the caps
+        if !equal(a, 0.0) {
+            let b = 2.0 * ray.origin.x * ray.direction.x + 2.0 * ray.origin.z * ray.direction.z;
+            let c = ray.origin.x.powi(2) + ray.origin.z.powi(2) - 1.0;
+            let disc = b * b - 4.0 * a * c;
+            if disc < 0.0 {
+                return Intersections::default();
+            }
+
+            let t0 = (-b - disc.sqrt()) / (2.0 * a);
+            let t1 = (-b + disc.sqrt()) / (2.0 * a);
+            for t in [t0, t1] {
+                let y = ray.origin.y + t * ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::intersection(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        Intersections::intersections(xs)
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let dist = point.x.powi(2) + point.z.powi(2);
+
+        if dist < 1.0 && point.y >= self.maximum - EPSILON {
+            Tuple::vector(0.0, 1.0, 0.0)
+        } else if dist < 1.0 && point.y <= self.minimum + EPSILON {
+            Tuple::vector(0.0, -1.0, 0.0)
+        } else {
+            Tuple::vector(point.x, 0.0, point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::bounding_box(Tuple::point(-1.0, self.minimum, -1.0), Tuple::point(1.0, self.maximum, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ray(o: (f64, f64, f64), d: (f64, f64, f64)) -> Ray {
+        Ray::ray(Tuple::point(o.0, o.1, o.2), Tuple::vector(d.0, d.1, d.2).normalize())
+    }
+
+    #[test]
+    fn test_fn() {
+        let c = Cylinder::cylinder();
+        assert!(c.minimum == f64::NEG_INFINITY);
+        assert!(c.maximum == f64::INFINITY);
+        assert!(!c.closed);
+    }
+
+    #[test]
+    fn test_intersect_misses() {
+        let c = Cylinder::cylinder();
+        for (o, d) in [
+            ((1.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 0.0, -5.0), (1.0, 1.0, 1.0)),
+        ] {
+            assert!(c.local_intersect(&ray(o, d)).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_intersect_hits() {
+        let c = Cylinder::cylinder();
+        for (o, d, t0, t1) in [
+            ((1.0, 0.0, -5.0), (0.0, 0.0, 1.0), 5.0, 5.0),
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 4.0, 6.0),
+            ((0.5, 0.0, -5.0), (0.1, 1.0, 1.0), 6.80798, 7.08872),
+        ] {
+            let xs = c.local_intersect(&ray(o, d));
+            assert_eq!(xs.len(), 2);
+            assert!(equal(xs[0].t, t0));
+            assert!(equal(xs[1].t, t1));
+        }
+    }
+
+    #[test]
+    fn test_normal() {
+        let c = Cylinder::cylinder();
+        for (p, n) in [
+            ((1.0, 0.0, 0.0), (1.0, 0.0, 0.0)),
+            ((0.0, 5.0, -1.0), (0.0, 0.0, -1.0)),
+            ((0.0, -2.0, 1.0), (0.0, 0.0, 1.0)),
+            ((-1.0, 1.0, 0.0), (-1.0, 0.0, 0.0)),
+        ] {
+            let normal = c.local_normal_at(Tuple::point(p.0, p.1, p.2));
+            assert_eq!(normal, Tuple::vector(n.0, n.1, n.2));
+        }
+    }
+
+    #[test]
+    fn test_intersect_truncated() {
+        let mut c = Cylinder::cylinder();
+        c.minimum = 1.0;
+        c.maximum = 2.0;
+        for (o, d, count) in [
+            ((0.0, 1.5, 0.0), (0.1, 1.0, 0.0), 0),
+            ((0.0, 3.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 2.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 1.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 1.5, -2.0), (0.0, 0.0, 1.0), 2),
+        ] {
+            assert_eq!(c.local_intersect(&ray(o, d)).len(), count);
+        }
+    }
+
+    #[test]
+    fn test_intersect_caps() {
+        let mut c = Cylinder::cylinder();
+        c.minimum = 1.0;
+        c.maximum = 2.0;
+        c.closed = true;
+        for (o, d, count) in [
+            ((0.0, 3.0, 0.0), (0.0, -1.0, 0.0), 2),
+            ((0.0, 3.0, -2.0), (0.0, -1.0, 2.0), 2),
+            ((0.0, 4.0, -2.0), (0.0, -1.0, 1.0), 2),
+            ((0.0, 0.0, -2.0), (0.0, 1.0, 2.0), 2),
+            ((0.0, -1.0, -2.0), (0.0, 1.0, 1.0), 2),
+        ] {
+            assert_eq!(c.local_intersect(&ray(o, d)).len(), count);
+        }
+    }
+
+    #[test]
+    fn test_normal_caps() {
+        let mut c = Cylinder::cylinder();
+        c.minimum = 1.0;
+        c.maximum = 2.0;
+        c.closed = true;
+        for (p, n) in [
+            ((0.0, 1.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.5, 1.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.0, 1.0, 0.5), (0.0, -1.0, 0.0)),
+            ((0.0, 2.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.5, 2.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 2.0, 0.5), (0.0, 1.0, 0.0)),
+        ] {
+            let normal = c.local_normal_at(Tuple::point(p.0, p.1, p.2));
+            assert_eq!(normal, Tuple::vector(n.0, n.1, n.2));
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = Cylinder::cylinder().bounds();
+        assert!(b.min.y == f64::NEG_INFINITY && b.max.y == f64::INFINITY);
+        assert!(b.min.x == -1.0 && b.max.z == 1.0);
+
+        let mut cyl = Cylinder::cylinder();
+        cyl.minimum = -5.0;
+        cyl.maximum = 3.0;
+        let b = cyl.bounds();
+        assert_eq!(b.min, Tuple::point(-1.0, -5.0, -1.0));
+        assert_eq!(b.max, Tuple::point(1.0, 3.0, 1.0));
+    }
+}
@@ -0,0 +1,431 @@
+use std::sync::Arc;
+
+use crate::bounds::BoundingBox;
+use crate::intersections::Intersections;
+use crate::matrices::Matrix;
+use crate::rays::Ray;
+use crate::shapes::{Parent, Shape, ShapeProps};
+use crate::tuples::Tuple;
+
+/// Children moved in and out of a group when it is divided.
+pub type Children = Vec<Box<dyn Shape>>;
+
+/// A collection of shapes that are transformed and intersected as a unit.
+///
+/// Children are told about the group's transform (and those of the groups
+/// above it) when they are added, and again whenever it changes, so they can
+/// convert points and normals between world and object space on their own.
+///
+/// The group keeps a box around its children and only intersects them when a
+/// ray hits that box; `divide` nests them into a hierarchy of such boxes.
+#[derive(Debug)]
+pub struct Group {
+    props: ShapeProps,
+    children: Vec<Box<dyn Shape>>,
+    // the link handed to every child, kept in sync with `props`
+    link: Arc<Parent>,
+    // the union of the children's boxes, grown as they are added
+    bounds: BoundingBox,
+}
+
+impl Group {
+    pub fn group() -> Group {
+        let props = ShapeProps::shape_props();
+        Group {
+            link: Arc::new(Parent::parent(&props)),
+            props,
+            children: Vec::new(),
+            bounds: BoundingBox::empty(),
+        }
+    }
+
+    pub fn add_child(&mut self, mut child: Box<dyn Shape>) {
+        child.set_parent(Some(self.link.clone()));
+        self.bounds.merge(&child.parent_space_bounds());
+        self.children.push(child);
+    }
+
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Takes out the children that fit entirely in either half of the
+    /// group's box, returning them as `(left, right)`. Children straddling
+    /// both halves stay in the group.
+    pub fn partition_children(&mut self) -> (Children, Children) {
+        let (left_box, right_box) = self.bounds.split();
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut remaining = Vec::new();
+        for child in self.children.drain(..) {
+            let b = child.parent_space_bounds();
+            if left_box.contains_box(&b) {
+                left.push(child);
+            } else if right_box.contains_box(&b) {
+                right.push(child);
+            } else {
+                remaining.push(child);
+            }
+        }
+        self.children = remaining;
+        (left, right)
+    }
+
+    /// Wraps `children` in a new group and adds it to this one.
+    pub fn make_subgroup(&mut self, children: Children) {
+        let mut subgroup = Group::group();
+        for child in children {
+            subgroup.add_child(child);
+        }
+        self.add_child(Box::new(subgroup));
+    }
+
+    fn update_children(&mut self) {
+        self.link = Arc::new(Parent::parent(&self.props));
+        for child in self.children.iter_mut() {
+            child.set_parent(Some(self.link.clone()));
+        }
+    }
+}
+
+impl Default for Group {
+    fn default() -> Group {
+        Group::group()
+    }
+}
+
+impl Shape for Group {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn set_transform(&mut self, m: Matrix) {
+        self.props.set_transform(m);
+        self.update_children();
+    }
+
+    fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.props.set_parent(parent);
+        self.update_children();
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut xs = Intersections::default();
+        if !self.bounds.intersects(ray) {
+            return xs;
+        }
+        for child in self.children.iter() {
+            xs.extend(child.intersect(ray));
+        }
+        xs
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        if threshold <= self.children.len() {
+            let (left, right) = self.partition_children();
+            // degenerate boxes can send every child to the same half, which
+            // would just nest the same group forever
+            if self.children.is_empty() && (left.is_empty() || right.is_empty()) {
+                self.children = left.into_iter().chain(right).collect();
+            } else {
+                if !left.is_empty() {
+                    self.make_subgroup(left);
+                }
+                if !right.is_empty() {
+                    self.make_subgroup(right);
+                }
+            }
+        }
+        for child in self.children.iter_mut() {
+            child.divide(threshold);
+        }
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.children.iter().any(|c| c.includes(other))
+    }
+
+    /// Intersections always point at a group's children, never at the group.
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        panic!("groups have no normals of their own")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::Matrix;
+    use crate::cylinders::Cylinder;
+    use crate::spheres::Sphere;
+    use crate::transformations::{rotation_y, scaling, translation};
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_fn() {
+        let g = Group::group();
+        assert_eq!(*g.transform(), Matrix::identity());
+        assert!(g.is_empty());
+    }
+
+    #[test]
+    fn test_add_child() {
+        let mut g = Group::group();
+        g.add_child(Box::new(Sphere::sphere()));
+        assert_eq!(g.len(), 1);
+    }
+
+    #[test]
+    fn test_intersect_empty() {
+        let g = Group::group();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(g.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn test_intersect_children() {
+        let mut g = Group::group();
+        let s1 = Sphere::sphere();
+        let mut s2 = Sphere::sphere();
+        s2.set_transform(translation(0.0, 0.0, -3.0));
+        let mut s3 = Sphere::sphere();
+        s3.set_transform(translation(5.0, 0.0, 0.0));
+        g.add_child(Box::new(s1));
+        g.add_child(Box::new(s2));
+        g.add_child(Box::new(s3));
+
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.local_intersect(&r);
+        assert_eq!(xs.len(), 4);
+        assert!(std::ptr::addr_eq(xs[0].object, g.children()[1].as_ref()));
+        assert!(std::ptr::addr_eq(xs[1].object, g.children()[1].as_ref()));
+        assert!(std::ptr::addr_eq(xs[2].object, g.children()[0].as_ref()));
+        assert!(std::ptr::addr_eq(xs[3].object, g.children()[0].as_ref()));
+    }
+
+    #[test]
+    fn test_intersect_transformed() {
+        let mut g = Group::group();
+        g.set_transform(scaling(2.0, 2.0, 2.0));
+        let mut s = Sphere::sphere();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        g.add_child(Box::new(s));
+
+        let r = Ray::ray(Tuple::point(10.0, 0.0, -10.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(g.intersect(&r).len(), 2);
+    }
+
+    // g1 (rotated) contains g2 (scaled) contains a translated sphere
+    fn nested(x: f64, y: f64, z: f64) -> Group {
+        let mut g1 = Group::group();
+        g1.set_transform(rotation_y(FRAC_PI_2));
+        let mut g2 = Group::group();
+        g2.set_transform(scaling(x, y, z));
+        let mut s = Sphere::sphere();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        g2.add_child(Box::new(s));
+        g1.add_child(Box::new(g2));
+        g1
+    }
+
+    fn nested_sphere(g1: &Group) -> &dyn Shape {
+        let g2 = g1.children()[0].as_ref();
+        // aim at the sphere's center in g2's parent space
+        let x = g2.transform()[(0, 0)] * 5.0;
+        let xs = g2.intersect(&Ray::ray(Tuple::point(x, 0.0, -10.0), Tuple::vector(0.0, 0.0, 1.0)));
+        xs[0].object
+    }
+
+    #[test]
+    fn test_world_to_object() {
+        let g1 = nested(2.0, 2.0, 2.0);
+        let s = nested_sphere(&g1);
+        let p = s.world_to_object(Tuple::point(-2.0, 0.0, -10.0));
+        assert!(p.approx_eq(&Tuple::point(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn test_normal_to_world() {
+        let g1 = nested(1.0, 2.0, 3.0);
+        let s = nested_sphere(&g1);
+        let v = 3.0_f64.sqrt() / 3.0;
+        let n = s.normal_to_world(Tuple::vector(v, v, v));
+        assert!((n.x - 0.2857).abs() < 0.0001);
+        assert!((n.y - 0.4286).abs() < 0.0001);
+        assert!((n.z + 0.8571).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_normal_on_child() {
+        let g1 = nested(1.0, 2.0, 3.0);
+        let s = nested_sphere(&g1);
+        let n = s.normal_at(Tuple::point(1.7321, 1.1547, -5.5774));
+        assert!((n.x - 0.2857).abs() < 0.0001);
+        assert!((n.y - 0.4286).abs() < 0.0001);
+        assert!((n.z + 0.8571).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_transform_after_children() {
+        // changing a group's transform after filling it reaches the children
+        let mut g = Group::group();
+        g.add_child(Box::new(Sphere::sphere()));
+        g.set_transform(translation(0.0, 0.0, 10.0));
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        let n = xs[0].object.normal_at(r.position(xs[0].t));
+        assert!(n.approx_eq(&Tuple::vector(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn test_only_set_transform_moves_children() {
+        // the transform can't be reached through props_mut, so editing the
+        // props leaves the children where they are
+        let mut g = Group::group();
+        g.add_child(Box::new(Sphere::sphere()));
+        g.props_mut().material.ambient = 0.5;
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(g.intersect(&r).len(), 2);
+
+        g.set_transform(translation(5.0, 0.0, 0.0));
+        assert_eq!(*g.props().transform(), translation(5.0, 0.0, 0.0));
+        assert!(g.intersect(&r).is_empty());
+        let b = g.parent_space_bounds();
+        assert_eq!((b.min.x, b.max.x), (4.0, 6.0));
+    }
+
+    fn sphere_at(x: f64, y: f64, z: f64) -> Box<dyn Shape> {
+        let mut s = Sphere::sphere();
+        s.set_transform(translation(x, y, z));
+        Box::new(s)
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut s = Sphere::sphere();
+        s.set_transform(&translation(2.0, 5.0, -3.0) * &scaling(2.0, 2.0, 2.0));
+        let mut c = Cylinder::cylinder();
+        c.minimum = -2.0;
+        c.maximum = 2.0;
+        c.set_transform(&translation(-4.0, -1.0, 4.0) * &scaling(0.5, 1.0, 0.5));
+        let mut g = Group::group();
+        g.add_child(Box::new(s));
+        g.add_child(Box::new(c));
+        let b = g.bounds();
+        assert_eq!(b.min, Tuple::point(-4.5, -3.0, -5.0));
+        assert_eq!(b.max, Tuple::point(4.0, 7.0, 4.5));
+
+        // an empty group adds nothing, even once transformed
+        let mut empty = Group::group();
+        empty.set_transform(rotation_y(0.5));
+        g.add_child(Box::new(empty));
+        assert_eq!(g.bounds(), b);
+    }
+
+    #[test]
+    fn test_intersect_skips_children_outside_bounds() {
+        let mut g = Group::group();
+        g.add_child(sphere_at(0.0, 0.0, 0.0));
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0));
+        assert!(!g.bounds().intersects(&r));
+        assert!(g.intersect(&r).is_empty());
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(g.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn test_partition_children() {
+        let mut g = Group::group();
+        g.add_child(sphere_at(-2.0, 0.0, 0.0));
+        g.add_child(sphere_at(2.0, 0.0, 0.0));
+        g.add_child(Box::new(Sphere::sphere()));
+        let (left, right) = g.partition_children();
+        assert_eq!(g.len(), 1);
+        assert_eq!(left.len(), 1);
+        assert_eq!(right.len(), 1);
+        assert_eq!(*left[0].transform(), translation(-2.0, 0.0, 0.0));
+        assert_eq!(*right[0].transform(), translation(2.0, 0.0, 0.0));
+        assert_eq!(*g.children()[0].transform(), Matrix::identity());
+    }
+
+    #[test]
+    fn test_make_subgroup() {
+        let mut g = Group::group();
+        g.make_subgroup(vec![sphere_at(0.0, 0.0, 0.0), sphere_at(0.0, 0.0, 0.0)]);
+        assert_eq!(g.len(), 1);
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(g.intersect(&r).len(), 4);
+    }
+
+    #[test]
+    fn test_divide() {
+        let mut g = Group::group();
+        g.add_child(sphere_at(-2.0, -2.0, 0.0));
+        g.add_child(sphere_at(-2.0, 2.0, 0.0));
+        let mut big = Sphere::sphere();
+        big.set_transform(scaling(4.0, 4.0, 4.0));
+        g.add_child(Box::new(big));
+        g.divide(1);
+
+        // the big sphere straddles both halves and stays put, while the two
+        // small ones end up in a subgroup that is split again
+        assert_eq!(g.len(), 2);
+        assert_eq!(*g.children()[0].transform(), scaling(4.0, 4.0, 4.0));
+        let r = Ray::ray(Tuple::point(-2.0, 2.0, -10.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = g.children()[1].intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(*xs[0].object.transform(), translation(-2.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_divide_below_threshold() {
+        let mut g = Group::group();
+        g.add_child(sphere_at(-2.0, 0.0, 0.0));
+        g.add_child(sphere_at(2.0, 0.0, 0.0));
+        g.divide(3);
+        assert_eq!(g.len(), 2);
+    }
+
+    #[test]
+    fn test_divide_keeps_intersections() {
+        let mut g = Group::group();
+        for i in 0..10 {
+            for j in 0..10 {
+                g.add_child(sphere_at(i as f64 * 3.0, j as f64 * 3.0, 0.0));
+            }
+        }
+        let rays: Vec<Ray> = (0..30)
+            .map(|i| Ray::ray(Tuple::point(i as f64, 13.0, -10.0), Tuple::vector(0.0, 0.0, 1.0)))
+            .collect();
+        let before: Vec<usize> = rays.iter().map(|r| g.intersect(r).len()).collect();
+        g.divide(4);
+        assert!(g.len() < 100);
+        let after: Vec<usize> = rays.iter().map(|r| g.intersect(r).len()).collect();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_includes() {
+        let mut g = Group::group();
+        g.add_child(Box::new(Sphere::sphere()));
+        let other = Sphere::sphere();
+        assert!(g.includes(g.children()[0].as_ref()));
+        assert!(!g.includes(&other));
+    }
+}
@@ -0,0 +1,356 @@
+use std::ops::Index;
+
+use crate::rays::Ray;
+use crate::shapes::Shape;
+use crate::tuples::{Tuple, EPSILON};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Intersection<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    /// Barycentric coordinates of the hit, only meaningful for triangles.
+    pub u: f64,
+    pub v: f64,
+}
+
+/// State shared by the shading routines, precomputed once per hit.
+#[derive(Debug, Clone)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    pub point: Tuple,
+    pub eyev: Tuple,
+    pub normalv: Tuple,
+    pub inside: bool,
+    /// The ray's direction mirrored about the normal.
+    pub reflectv: Tuple,
+    /// `point` nudged along the normal, to avoid self-shadowing acne.
+    pub over_point: Tuple,
+    /// `point` nudged against the normal, where refracted rays start.
+    pub under_point: Tuple,
+    pub n1: f64,
+    pub n2: f64,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn intersection(t: f64, object: &'a dyn Shape) -> Intersection<'a> {
+        Intersection::intersection_with_uv(t, object, 0.0, 0.0)
+    }
+
+    pub fn intersection_with_uv(t: f64, object: &'a dyn Shape, u: f64, v: f64) -> Intersection<'a> {
+        Intersection { t, object, u, v }
+    }
+
+    /// `xs` is the full list of intersections along `ray`, used to find the
+    /// refractive indices on both sides of this hit.
+    pub fn prepare_computations(&self, ray: &Ray, xs: &Intersections<'a>) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at_hit(point, self);
+        let inside = normalv.dot(&eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+
+        let reflectv = ray.direction.reflect(&normalv);
+        let (n1, n2) = self.refractive_indices(xs);
+
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            eyev,
+            normalv,
+            inside,
+            reflectv,
+            over_point: point + normalv * EPSILON,
+            under_point: point - normalv * EPSILON,
+            n1,
+            n2,
+        }
+    }
+
+    // Walks the intersections up to this one, tracking which objects the ray is
+    // currently inside of.
+    fn refractive_indices(&self, xs: &Intersections<'a>) -> (f64, f64) {
+        let mut containers: Vec<&dyn Shape> = Vec::new();
+        let mut n1 = 1.0;
+        let mut n2 = 1.0;
+
+        for i in xs.list.iter() {
+            let is_hit = i == self;
+            if is_hit {
+                n1 = containers.last().map_or(1.0, |o| o.material().refractive_index);
+            }
+
+            match containers.iter().position(|o| std::ptr::addr_eq(*o, i.object)) {
+                Some(idx) => {
+                    containers.remove(idx);
+                }
+                None => containers.push(i.object),
+            }
+
+            if is_hit {
+                n2 = containers.last().map_or(1.0, |o| o.material().refractive_index);
+                break;
+            }
+        }
+        (n1, n2)
+    }
+}
+
+impl Computations<'_> {
+    /// Schlick's approximation of the Fresnel effect: the fraction of light
+    /// reflected rather than refracted at the hit.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(&self.normalv);
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            // going from a denser medium, use the angle of the transmitted ray
+            cos = (1.0 - sin2_t).sqrt();
+        }
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+}
+
+impl PartialEq for Intersection<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.t == other.t && std::ptr::addr_eq(self.object, other.object)
+    }
+}
+
+/// A list of intersections, always kept sorted by `t`.
+#[derive(Debug, Default, Clone)]
+pub struct Intersections<'a> {
+    pub list: Vec<Intersection<'a>>,
+}
+
+impl<'a> Intersections<'a> {
+    pub fn intersections(mut list: Vec<Intersection<'a>>) -> Intersections<'a> {
+        list.sort_by(|a, b| a.t.total_cmp(&b.t));
+        Intersections { list }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Merges another set of intersections into this one, keeping the order.
+    pub fn extend(&mut self, other: Intersections<'a>) {
+        self.list.extend(other.list);
+        self.list.sort_by(|a, b| a.t.total_cmp(&b.t));
+    }
+
+    /// The visible intersection: the one with the lowest non-negative `t`.
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
+        self.list.iter().find(|i| i.t >= 0.0)
+    }
+}
+
+impl<'a> Index<usize> for Intersections<'a> {
+    type Output = Intersection<'a>;
+
+    fn index(&self, i: usize) -> &Intersection<'a> {
+        &self.list[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuples::equal;
+    use crate::planes::Plane;
+    use crate::spheres::Sphere;
+    use std::f64::consts::FRAC_1_SQRT_2;
+    use crate::transformations::{scaling, translation};
+
+    #[test]
+    fn test_fn() {
+        let s = Sphere::sphere();
+        let i = Intersection::intersection(3.5, &s);
+        assert!(i.t == 3.5);
+        assert!(std::ptr::addr_eq(i.object, &s));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let s = Sphere::sphere();
+        let i1 = Intersection::intersection(2.0, &s);
+        let i2 = Intersection::intersection(1.0, &s);
+        let xs = Intersections::intersections(vec![i1, i2]);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == 1.0);
+        assert!(xs[1].t == 2.0);
+    }
+
+    #[test]
+    fn test_hit() {
+        let s = Sphere::sphere();
+        let i1 = Intersection::intersection(1.0, &s);
+        let i2 = Intersection::intersection(2.0, &s);
+        let xs = Intersections::intersections(vec![i2, i1]);
+        assert_eq!(xs.hit(), Some(&i1));
+
+        let i1 = Intersection::intersection(-1.0, &s);
+        let i2 = Intersection::intersection(1.0, &s);
+        let xs = Intersections::intersections(vec![i2, i1]);
+        assert_eq!(xs.hit(), Some(&i2));
+
+        let i1 = Intersection::intersection(-2.0, &s);
+        let i2 = Intersection::intersection(-1.0, &s);
+        let xs = Intersections::intersections(vec![i2, i1]);
+        assert_eq!(xs.hit(), None);
+
+        let i1 = Intersection::intersection(5.0, &s);
+        let i2 = Intersection::intersection(7.0, &s);
+        let i3 = Intersection::intersection(-3.0, &s);
+        let i4 = Intersection::intersection(2.0, &s);
+        let xs = Intersections::intersections(vec![i1, i2, i3, i4]);
+        assert_eq!(xs.hit(), Some(&i4));
+    }
+
+    #[test]
+    fn test_extend() {
+        let s = Sphere::sphere();
+        let mut xs = Intersections::intersections(vec![
+            Intersection::intersection(4.0, &s),
+            Intersection::intersection(1.0, &s),
+        ]);
+        xs.extend(Intersections::intersections(vec![
+            Intersection::intersection(2.0, &s),
+            Intersection::intersection(-1.0, &s),
+        ]));
+        let ts: Vec<f64> = xs.list.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![-1.0, 1.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_prepare_computations() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::sphere();
+        let i = Intersection::intersection(4.0, &s);
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(comps.t == i.t);
+        assert!(std::ptr::addr_eq(comps.object, &s));
+        assert_eq!(comps.point, Tuple::point(0.0, 0.0, -1.0));
+        assert_eq!(comps.eyev, Tuple::vector(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv, Tuple::vector(0.0, 0.0, -1.0));
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn test_prepare_computations_inside() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::sphere();
+        let i = Intersection::intersection(1.0, &s);
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert_eq!(comps.point, Tuple::point(0.0, 0.0, 1.0));
+        assert_eq!(comps.eyev, Tuple::vector(0.0, 0.0, -1.0));
+        assert!(comps.inside);
+        // normal is inverted
+        assert_eq!(comps.normalv, Tuple::vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_over_under_point() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let mut s = Sphere::glass_sphere();
+        s.set_transform(translation(0.0, 0.0, 1.0));
+        let i = Intersection::intersection(5.0, &s);
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(comps.over_point.z < -EPSILON / 2.0);
+        assert!(comps.point.z > comps.over_point.z);
+        assert!(comps.under_point.z > EPSILON / 2.0);
+        assert!(comps.point.z < comps.under_point.z);
+    }
+
+    #[test]
+    fn test_refractive_indices() {
+        let mut a = Sphere::glass_sphere();
+        a.set_transform(scaling(2.0, 2.0, 2.0));
+        a.material_mut().refractive_index = 1.5;
+        let mut b = Sphere::glass_sphere();
+        b.set_transform(translation(0.0, 0.0, -0.25));
+        b.material_mut().refractive_index = 2.0;
+        let mut c = Sphere::glass_sphere();
+        c.set_transform(translation(0.0, 0.0, 0.25));
+        c.material_mut().refractive_index = 2.5;
+
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -4.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(2.0, &a),
+            Intersection::intersection(2.75, &b),
+            Intersection::intersection(3.25, &c),
+            Intersection::intersection(4.75, &b),
+            Intersection::intersection(5.25, &c),
+            Intersection::intersection(6.0, &a),
+        ]);
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+        for (i, (n1, n2)) in expected.iter().enumerate() {
+            let comps = xs[i].prepare_computations(&r, &xs);
+            assert!(comps.n1 == *n1);
+            assert!(comps.n2 == *n2);
+        }
+    }
+
+    #[test]
+    fn test_reflectv() {
+        let shape = Plane::plane();
+        let r = Ray::ray(
+            Tuple::point(0.0, 1.0, -1.0),
+            Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::intersection(2.0_f64.sqrt(), &shape);
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(comps.reflectv.approx_eq(&Tuple::vector(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2)));
+    }
+
+    #[test]
+    fn test_schlick_total_internal_reflection() {
+        let shape = Sphere::glass_sphere();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, FRAC_1_SQRT_2), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(-FRAC_1_SQRT_2, &shape),
+            Intersection::intersection(FRAC_1_SQRT_2, &shape),
+        ]);
+        let comps = xs[1].prepare_computations(&r, &xs);
+        assert!(comps.schlick() == 1.0);
+    }
+
+    #[test]
+    fn test_schlick_perpendicular() {
+        let shape = Sphere::glass_sphere();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(-1.0, &shape),
+            Intersection::intersection(1.0, &shape),
+        ]);
+        let comps = xs[1].prepare_computations(&r, &xs);
+        assert!(equal(comps.schlick(), 0.04));
+    }
+
+    #[test]
+    fn test_schlick_small_angle() {
+        let shape = Sphere::glass_sphere();
+        let r = Ray::ray(Tuple::point(0.0, 0.99, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = Intersections::intersections(vec![Intersection::intersection(1.8589, &shape)]);
+        let comps = xs[0].prepare_computations(&r, &xs);
+        assert!((comps.schlick() - 0.48873).abs() < 0.0001);
+    }
+}
@@ -1,7 +1,60 @@
 pub mod tuples;
 pub mod colors;
 pub mod canvas;
+pub mod ppm;
+pub mod png;
+pub mod matrices;
+pub mod transformations;
+pub mod rays;
+pub mod bounds;
+pub mod shapes;
+pub mod spheres;
+pub mod planes;
+pub mod cubes;
+pub mod cylinders;
+pub mod cones;
+pub mod triangles;
+pub mod groups;
+pub mod csg;
+pub mod obj;
+pub mod intersections;
+pub mod materials;
+pub mod noise;
+pub mod patterns;
+pub mod textures;
+pub mod sequences;
+pub mod lights;
+pub mod world;
+pub mod antialiasing;
+pub mod camera;
+pub mod scene;
 
 pub use tuples::Tuple; 
-pub use colors::Color; 
-pub use canvas::Canvas; 
\ No newline at end of file
+pub use colors::{Color, Encoding, Quantizer};
+pub use canvas::Canvas;
+pub use ppm::{PpmError, PpmFormat};
+pub use matrices::Matrix;
+pub use rays::Ray;
+pub use bounds::BoundingBox;
+pub use shapes::Shape;
+pub use spheres::Sphere;
+pub use planes::Plane;
+pub use cubes::Cube;
+pub use cylinders::Cylinder;
+pub use cones::Cone;
+pub use triangles::{SmoothTriangle, Triangle};
+pub use groups::Group;
+pub use csg::{Csg, CsgOperation};
+pub use intersections::{Computations, Intersection, Intersections};
+pub use materials::Material;
+pub use patterns::{
+    BlendedPattern, CheckersPattern, GradientPattern, Pattern, PerturbedPattern, RadialGradientPattern,
+    RingPattern, SolidPattern, StripePattern,
+};
+pub use textures::{CubeMapPattern, TextureMapPattern, UvAlignCheck, UvCheckers, UvImage, UvMapping, UvPattern};
+pub use lights::{AreaLight, DirectionalLight, Illumination, Light, LightSample, PointLight, SpotLight};
+pub use sequences::{CyclicSequence, RandomSequence, Sequence};
+pub use world::World;
+pub use antialiasing::{Antialiasing, Filter, SampleLayout};
+pub use camera::Camera;
+pub use scene::{load_scene, parse_scene, Scene, SceneError, SceneErrorKind}; 
\ No newline at end of file
@@ -0,0 +1,701 @@
+use crate::colors::Color;
+use crate::materials::Material;
+use crate::sequences::Sequence;
+use crate::shapes::Shape;
+use crate::tuples::Tuple;
+use crate::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Tuple,
+    pub intensity: Color,
+}
+
+impl PointLight {
+    pub fn point_light(position: Tuple, intensity: Color) -> PointLight {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+}
+
+/// A rectangular light, sampled on a `usteps` by `vsteps` grid of cells to
+/// give soft shadows. Each sample is the middle of its cell, or with
+/// `jitter` a spot in it picked by the sequence the caller samples with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaLight {
+    pub corner: Tuple,
+    /// One cell's edge along the first side.
+    pub uvec: Tuple,
+    pub usteps: usize,
+    /// One cell's edge along the second side.
+    pub vvec: Tuple,
+    pub vsteps: usize,
+    pub intensity: Color,
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    /// A light spanning `full_uvec` and `full_vvec` from `corner`.
+    pub fn area_light(
+        corner: Tuple,
+        full_uvec: Tuple,
+        usteps: usize,
+        full_vvec: Tuple,
+        vsteps: usize,
+        intensity: Color,
+    ) -> AreaLight {
+        assert!(usteps > 0 && vsteps > 0, "an area light needs at least one cell");
+        AreaLight {
+            corner,
+            uvec: full_uvec / usteps as f64,
+            usteps,
+            vvec: full_vvec / vsteps as f64,
+            vsteps,
+            intensity,
+            jitter: false,
+        }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.usteps * self.vsteps
+    }
+
+    /// The middle of the light.
+    pub fn position(&self) -> Tuple {
+        self.corner + self.uvec * (self.usteps as f64 / 2.0) + self.vvec * (self.vsteps as f64 / 2.0)
+    }
+
+    /// A point in cell (`u`, `v`), drawing its offsets along u and then v
+    /// from `sampler` when the light is jittered.
+    pub fn point_on_light(&self, u: usize, v: usize, sampler: &mut dyn Sequence) -> Tuple {
+        let (du, dv) = if self.jitter {
+            (sampler.next(), sampler.next())
+        } else {
+            (0.5, 0.5)
+        };
+        self.corner + self.uvec * (u as f64 + du) + self.vvec * (v as f64 + dv)
+    }
+}
+
+/// A light infinitely far away, like the sun: every point sees it from the
+/// same direction, and nothing can be behind it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    /// The direction the light travels in, normalized.
+    pub direction: Tuple,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn directional_light(direction: Tuple, intensity: Color) -> DirectionalLight {
+        DirectionalLight {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+/// A point light that only shines inside a cone around `direction`. Points
+/// within `inner_angle` of the axis get the full intensity, which fades
+/// smoothly to nothing at `outer_angle`. Angles are in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    pub position: Tuple,
+    /// The axis of the cone, normalized.
+    pub direction: Tuple,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Color,
+}
+
+impl SpotLight {
+    pub fn spot_light(
+        position: Tuple,
+        direction: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Color,
+    ) -> SpotLight {
+        SpotLight {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+        }
+    }
+
+    /// The fraction of the light that `point` gets for where it sits in the
+    /// cone, ignoring shadows.
+    pub fn falloff(&self, point: Tuple) -> f64 {
+        let cos = (point - self.position).normalize().dot(&self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+        if cos >= cos_inner {
+            return 1.0;
+        }
+        if cos <= cos_outer {
+            return 0.0;
+        }
+        // smoothstep between the two cones
+        let t = (cos - cos_outer) / (cos_inner - cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+/// The direction from a point toward one sample of a light, and how far away
+/// that sample is (infinite for directional lights).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSample {
+    pub lightv: Tuple,
+    pub distance: f64,
+}
+
+impl LightSample {
+    fn toward(position: Tuple, point: Tuple) -> LightSample {
+        let v = position - point;
+        LightSample {
+            lightv: v.normalize(),
+            distance: v.magnitude(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Light {
+    Point(PointLight),
+    Area(AreaLight),
+    Directional(DirectionalLight),
+    Spot(SpotLight),
+}
+
+impl Light {
+    pub fn intensity(&self) -> Color {
+        match self {
+            Light::Point(light) => light.intensity,
+            Light::Area(light) => light.intensity,
+            Light::Directional(light) => light.intensity,
+            Light::Spot(light) => light.intensity,
+        }
+    }
+
+    /// Where the light is, or its middle for lights with an extent.
+    /// Directional lights have no position.
+    pub fn position(&self) -> Option<Tuple> {
+        match self {
+            Light::Point(light) => Some(light.position),
+            Light::Area(light) => Some(light.position()),
+            Light::Directional(_) => None,
+            Light::Spot(light) => Some(light.position),
+        }
+    }
+
+    /// The samples that stand in for the light, as seen from `point`, when
+    /// shading and casting shadows. Jittered lights draw from `sampler`.
+    pub fn samples_from(&self, point: Tuple, sampler: &mut dyn Sequence) -> Vec<LightSample> {
+        match self {
+            Light::Point(light) => vec![LightSample::toward(light.position, point)],
+            Light::Area(light) => {
+                let mut samples = Vec::with_capacity(light.samples());
+                for v in 0..light.vsteps {
+                    for u in 0..light.usteps {
+                        samples.push(LightSample::toward(light.point_on_light(u, v, sampler), point));
+                    }
+                }
+                samples
+            }
+            Light::Directional(light) => vec![LightSample {
+                lightv: -light.direction,
+                distance: f64::INFINITY,
+            }],
+            Light::Spot(light) => vec![LightSample::toward(light.position, point)],
+        }
+    }
+
+    /// How much of the light reaches `point` through `samples`, from 0
+    /// (fully shadowed, or outside a spot light's cone) to 1.
+    pub fn intensity_at(&self, point: Tuple, samples: &[LightSample], world: &World) -> f64 {
+        let lit = samples
+            .iter()
+            .filter(|s| !world.is_occluded(point, s.lightv, s.distance))
+            .count();
+        let visible = lit as f64 / samples.len() as f64;
+        match self {
+            Light::Spot(light) => visible * light.falloff(point),
+            _ => visible,
+        }
+    }
+
+    /// Samples the light from `point` and works out how much of it gets there.
+    pub fn illuminate(&self, point: Tuple, world: &World, sampler: &mut dyn Sequence) -> Illumination {
+        let samples = self.samples_from(point, sampler);
+        let intensity = self.intensity_at(point, &samples, world);
+        Illumination { samples, intensity }
+    }
+}
+
+/// What a point sees of a light: the samples standing in for it, and the
+/// fraction of the light reaching the point, as given by `Light::intensity_at`.
+/// Shadows and shading both use the same samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Illumination {
+    pub samples: Vec<LightSample>,
+    pub intensity: f64,
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Light {
+        Light::Point(light)
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(light: AreaLight) -> Light {
+        Light::Area(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Light {
+        Light::Directional(light)
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Light {
+        Light::Spot(light)
+    }
+}
+
+/// Shades a single point with the Phong reflection model. `object` is the
+/// shape being shaded, needed to find where `point` falls on its pattern.
+/// Diffuse and specular are averaged over the samples in `illumination` and
+/// scaled by its intensity.
+pub fn lighting(
+    material: &Material,
+    object: &dyn Shape,
+    light: &Light,
+    point: Tuple,
+    eyev: Tuple,
+    normalv: Tuple,
+    illumination: &Illumination,
+) -> Color {
+    let black = Color::color(0.0, 0.0, 0.0);
+    let color = match &material.pattern {
+        Some(pattern) => pattern.pattern_at_shape(object, point),
+        None => material.color,
+    };
+    let effective_color = color * light.intensity();
+    let ambient = effective_color * material.ambient;
+
+    let (samples, intensity) = (&illumination.samples, illumination.intensity);
+    if intensity == 0.0 || samples.is_empty() {
+        return ambient;
+    }
+
+    let mut sum = black;
+    for sample in samples.iter() {
+        let lightv = sample.lightv;
+
+        // a negative cosine means the light is on the other side of the surface
+        let light_dot_normal = lightv.dot(&normalv);
+        if light_dot_normal < 0.0 {
+            continue;
+        }
+        let diffuse = effective_color * material.diffuse * light_dot_normal;
+
+        let reflectv = (-lightv).reflect(&normalv);
+        let reflect_dot_eye = reflectv.dot(&eyev);
+        let specular = if reflect_dot_eye <= 0.0 {
+            black
+        } else {
+            light.intensity() * material.specular * reflect_dot_eye.powf(material.shininess)
+        };
+        sum = sum + diffuse + specular;
+    }
+
+    ambient + sum * (intensity / samples.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::StripePattern;
+    use crate::sequences::{CyclicSequence, RandomSequence};
+    use crate::tuples::equal;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+    use crate::spheres::Sphere;
+    use std::sync::Arc;
+
+    fn assert_color_eq(a: Color, b: Color) {
+        assert!(a.approx_eq(&b), "{:?} != {:?}", a, b);
+    }
+
+    fn centered() -> CyclicSequence {
+        CyclicSequence::cyclic_sequence(vec![0.5])
+    }
+
+    // the light as seen from `point`, with `intensity` of it getting there
+    fn lit(light: &Light, point: Tuple, intensity: f64) -> Illumination {
+        Illumination {
+            samples: light.samples_from(point, &mut centered()),
+            intensity,
+        }
+    }
+
+    fn setup() -> (Material, Tuple) {
+        (Material::material(), Tuple::point(0.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn test_fn() {
+        let intensity = Color::color(1.0, 1.0, 1.0);
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let light = PointLight::point_light(position, intensity);
+        assert_eq!(light.position, position);
+        assert_eq!(light.intensity, intensity);
+    }
+
+    #[test]
+    fn test_eye_between_light_and_surface() {
+        let (m, position) = setup();
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 0.0, -10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 1.0));
+        assert_color_eq(result, Color::color(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn test_eye_offset_45() {
+        let (m, position) = setup();
+        let h = 2.0_f64.sqrt() / 2.0;
+        let eyev = Tuple::vector(0.0, h, -h);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 0.0, -10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 1.0));
+        assert_color_eq(result, Color::color(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_light_offset_45() {
+        let (m, position) = setup();
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 10.0, -10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 1.0));
+        assert_color_eq(result, Color::color(0.7364, 0.7364, 0.7364));
+    }
+
+    #[test]
+    fn test_eye_in_reflection_path() {
+        let (m, position) = setup();
+        let h = 2.0_f64.sqrt() / 2.0;
+        let eyev = Tuple::vector(0.0, -h, -h);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 10.0, -10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 1.0));
+        assert_color_eq(result, Color::color(1.63639, 1.63639, 1.63639));
+    }
+
+    #[test]
+    fn test_light_behind_surface() {
+        let (m, position) = setup();
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 0.0, 10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 1.0));
+        assert_color_eq(result, Color::color(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn test_in_shadow() {
+        let (m, position) = setup();
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 0.0, -10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 0.0));
+        assert_color_eq(result, Color::color(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let mut m = Material::material();
+        m.pattern = Some(Arc::new(StripePattern::stripe_pattern(
+            Color::color(1.0, 1.0, 1.0),
+            Color::color(0.0, 0.0, 0.0),
+        )));
+        m.ambient = 1.0;
+        m.diffuse = 0.0;
+        m.specular = 0.0;
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light: Light = PointLight::point_light(Tuple::point(0.0, 0.0, -10.0), Color::color(1.0, 1.0, 1.0)).into();
+        let s = Sphere::sphere();
+        let (p1, p2) = (Tuple::point(0.9, 0.0, 0.0), Tuple::point(1.1, 0.0, 0.0));
+        let c1 = lighting(&m, &s, &light, p1, eyev, normalv, &lit(&light, p1, 1.0));
+        let c2 = lighting(&m, &s, &light, p2, eyev, normalv, &lit(&light, p2, 1.0));
+        assert_eq!(c1, Color::color(1.0, 1.0, 1.0));
+        assert_eq!(c2, Color::color(0.0, 0.0, 0.0));
+    }
+
+    fn area_light_2x2() -> AreaLight {
+        AreaLight::area_light(
+            Tuple::point(-0.5, -0.5, -5.0),
+            Tuple::vector(1.0, 0.0, 0.0),
+            2,
+            Tuple::vector(0.0, 1.0, 0.0),
+            2,
+            Color::color(1.0, 1.0, 1.0),
+        )
+    }
+
+    fn area_light_4x2() -> AreaLight {
+        AreaLight::area_light(
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(2.0, 0.0, 0.0),
+            4,
+            Tuple::vector(0.0, 0.0, 1.0),
+            2,
+            Color::color(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn test_area_light() {
+        let light = area_light_4x2();
+        assert_eq!(light.corner, Tuple::point(0.0, 0.0, 0.0));
+        assert_eq!(light.uvec, Tuple::vector(0.5, 0.0, 0.0));
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vvec, Tuple::vector(0.0, 0.0, 0.5));
+        assert_eq!(light.vsteps, 2);
+        assert_eq!(light.samples(), 8);
+        assert_eq!(light.position(), Tuple::point(1.0, 0.0, 0.5));
+
+        let light: Light = light.into();
+        assert_eq!(light.position(), Some(Tuple::point(1.0, 0.0, 0.5)));
+        assert_eq!(light.samples_from(Tuple::point(0.0, 5.0, 0.0), &mut centered()).len(), 8);
+    }
+
+    #[test]
+    fn test_point_on_light() {
+        let light = area_light_4x2();
+        let cases = [
+            (0, 0, (0.25, 0.0, 0.25)),
+            (1, 0, (0.75, 0.0, 0.25)),
+            (0, 1, (0.25, 0.0, 0.75)),
+            (2, 0, (1.25, 0.0, 0.25)),
+            (3, 1, (1.75, 0.0, 0.75)),
+        ];
+        for (u, v, (x, y, z)) in cases {
+            // without jitter the sampler is never drawn from
+            let mut sampler = CyclicSequence::cyclic_sequence(vec![0.1, 0.9]);
+            assert_eq!(light.point_on_light(u, v, &mut sampler), Tuple::point(x, y, z));
+            assert_eq!(sampler.next(), 0.1);
+        }
+    }
+
+    #[test]
+    fn test_point_on_jittered_light() {
+        let cases = [
+            (0, 0, (0.15, 0.0, 0.35)),
+            (1, 0, (0.65, 0.0, 0.35)),
+            (0, 1, (0.15, 0.0, 0.85)),
+            (2, 0, (1.15, 0.0, 0.35)),
+            (3, 1, (1.65, 0.0, 0.85)),
+        ];
+        for (u, v, (x, y, z)) in cases {
+            // the first number moves along u, the second along v
+            let mut light = area_light_4x2();
+            light.jitter = true;
+            let mut sampler = CyclicSequence::cyclic_sequence(vec![0.3, 0.7]);
+            assert_eq!(light.point_on_light(u, v, &mut sampler), Tuple::point(x, y, z));
+        }
+    }
+
+    #[test]
+    fn test_intensity_at_point_light() {
+        let w = World::default_world();
+        let light = &w.lights[0];
+        let cases = [
+            ((0.0, 1.0001, 0.0), 1.0),
+            ((-1.0001, 0.0, 0.0), 1.0),
+            ((0.0, 0.0, -1.0001), 1.0),
+            ((0.0, 0.0, 1.0001), 0.0),
+            ((1.0001, 0.0, 0.0), 0.0),
+            ((0.0, -1.0001, 0.0), 0.0),
+            ((0.0, 0.0, 0.0), 0.0),
+        ];
+        for ((x, y, z), expected) in cases {
+            let point = Tuple::point(x, y, z);
+            let samples = light.samples_from(point, &mut centered());
+            assert_eq!(light.intensity_at(point, &samples, &w), expected);
+        }
+    }
+
+    #[test]
+    fn test_intensity_at_area_light() {
+        let w = World::default_world();
+        let cases = [
+            ((0.0, 0.0, 2.0), 0.0, 0.0),
+            ((1.0, -1.0, 2.0), 0.25, 0.5),
+            ((1.5, 0.0, 2.0), 0.5, 0.75),
+            ((1.25, 1.25, 3.0), 0.75, 0.75),
+            ((0.0, 0.0, -2.0), 1.0, 1.0),
+        ];
+        for ((x, y, z), centered, jittered) in cases {
+            let point = Tuple::point(x, y, z);
+            let mut sampler = CyclicSequence::cyclic_sequence(vec![0.7, 0.3, 0.9, 0.1, 0.5]);
+            let light: Light = area_light_2x2().into();
+            assert!(equal(light.illuminate(point, &w, &mut sampler).intensity, centered));
+
+            let mut light = area_light_2x2();
+            light.jitter = true;
+            let light: Light = light.into();
+            let mut sampler = CyclicSequence::cyclic_sequence(vec![0.7, 0.3, 0.9, 0.1, 0.5]);
+            assert!(equal(light.illuminate(point, &w, &mut sampler).intensity, jittered));
+        }
+    }
+
+    #[test]
+    fn test_illuminate() {
+        let w = World::default_world();
+        let mut light = area_light_2x2();
+        light.jitter = true;
+        let light: Light = light.into();
+        let point = Tuple::point(1.0, -1.0, 2.0);
+
+        // the intensity comes from the very samples handed back for shading
+        let a = light.illuminate(point, &w, &mut RandomSequence::random_sequence(3));
+        assert_eq!(a.samples.len(), 4);
+        assert_eq!(a.intensity, light.intensity_at(point, &a.samples, &w));
+
+        // and the same sampler gives the same samples, however often it's asked
+        let b = light.illuminate(point, &w, &mut RandomSequence::random_sequence(3));
+        assert_eq!(a, b);
+        let c = light.illuminate(point, &w, &mut RandomSequence::random_sequence(4));
+        assert_ne!(a.samples, c.samples);
+    }
+
+    #[test]
+    fn test_lighting_uses_intensity() {
+        let mut w = World::default_world();
+        w.lights = vec![PointLight::point_light(Tuple::point(0.0, 0.0, -10.0), Color::color(1.0, 1.0, 1.0)).into()];
+        let mut m = Material::material();
+        m.ambient = 0.1;
+        m.diffuse = 0.9;
+        m.specular = 0.0;
+        let point = Tuple::point(0.0, 0.0, -1.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        for (intensity, expected) in [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)] {
+            let light = &w.lights[0];
+            let result = lighting(&m, w.objects[0].as_ref(), light, point, eyev, normalv, &lit(light, point, intensity));
+            assert_color_eq(result, Color::color(expected, expected, expected));
+        }
+    }
+
+    #[test]
+    fn test_lighting_samples_area_light() {
+        let light: Light = area_light_2x2().into();
+        let mut m = Material::material();
+        m.ambient = 0.1;
+        m.diffuse = 0.9;
+        m.specular = 0.0;
+        let s = Sphere::sphere();
+        let eye = Tuple::point(0.0, 0.0, -5.0);
+        let cases = [((0.0, 0.0, -1.0), 0.9965), ((0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2), 0.62318)];
+        for ((x, y, z), expected) in cases {
+            let point = Tuple::point(x, y, z);
+            let eyev = (eye - point).normalize();
+            let normalv = Tuple::vector(x, y, z);
+            let result = lighting(&m, &s, &light, point, eyev, normalv, &lit(&light, point, 1.0));
+            assert!((result.red - expected).abs() < 0.0001, "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn test_directional_light() {
+        let light = DirectionalLight::directional_light(Tuple::vector(0.0, 0.0, 2.0), Color::color(1.0, 1.0, 1.0));
+        assert_eq!(light.direction, Tuple::vector(0.0, 0.0, 1.0));
+
+        let light: Light = light.into();
+        assert_eq!(light.position(), None);
+        let samples = light.samples_from(Tuple::point(3.0, 4.0, 5.0), &mut centered());
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].lightv, Tuple::vector(0.0, 0.0, -1.0));
+        assert!(samples[0].distance.is_infinite());
+
+        // lit the same as a point light straight ahead
+        let (m, position) = setup();
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let result = lighting(&m, &Sphere::sphere(), &light, position, eyev, normalv, &lit(&light, position, 1.0));
+        assert_color_eq(result, Color::color(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn test_intensity_at_directional_light() {
+        let w = World::default_world();
+        let light: Light =
+            DirectionalLight::directional_light(Tuple::vector(0.0, 0.0, 1.0), Color::color(1.0, 1.0, 1.0)).into();
+        assert_eq!(light.illuminate(Tuple::point(0.0, 0.0, -2.0), &w, &mut centered()).intensity, 1.0);
+        assert_eq!(light.illuminate(Tuple::point(0.0, 0.0, 2.0), &w, &mut centered()).intensity, 0.0);
+        // however far the point is from the spheres
+        assert_eq!(light.illuminate(Tuple::point(0.0, 0.0, 1000.0), &w, &mut centered()).intensity, 0.0);
+        assert_eq!(light.illuminate(Tuple::point(0.0, 2.0, 1000.0), &w, &mut centered()).intensity, 1.0);
+    }
+
+    #[test]
+    fn test_spot_light_falloff() {
+        let light = SpotLight::spot_light(
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 0.0, 3.0),
+            PI / 6.0,
+            PI / 3.0,
+            Color::color(1.0, 1.0, 1.0),
+        );
+        assert_eq!(light.direction, Tuple::vector(0.0, 0.0, 1.0));
+        let cases = [
+            ((0.0, 0.0, 5.0), 1.0),
+            ((0.0, 1.0, 5.0), 1.0),
+            ((0.0, 5.0, 5.0), 0.59817),
+            ((5.0, 0.0, 1.0), 0.0),
+            ((0.0, 0.0, -5.0), 0.0),
+        ];
+        for ((x, y, z), expected) in cases {
+            assert!((light.falloff(Tuple::point(x, y, z)) - expected).abs() < 0.0001);
+        }
+
+        // a hard edge when both cones match
+        let light = SpotLight {
+            inner_angle: PI / 4.0,
+            outer_angle: PI / 4.0,
+            ..light
+        };
+        assert_eq!(light.falloff(Tuple::point(0.0, 0.9, 1.0)), 1.0);
+        assert_eq!(light.falloff(Tuple::point(0.0, 1.1, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn test_intensity_at_spot_light() {
+        let w = World::default_world();
+        let light: Light = SpotLight::spot_light(
+            Tuple::point(-10.0, 10.0, -10.0),
+            Tuple::vector(1.0, -1.0, 1.0),
+            PI / 12.0,
+            PI / 6.0,
+            Color::color(1.0, 1.0, 1.0),
+        )
+        .into();
+        assert_eq!(light.position(), Some(Tuple::point(-10.0, 10.0, -10.0)));
+        // in the cone and unobstructed
+        assert!(equal(light.illuminate(Tuple::point(-2.0, 2.0, -2.0), &w, &mut centered()).intensity, 1.0));
+        // unobstructed but outside the cone
+        assert_eq!(light.illuminate(Tuple::point(0.0, 10.0, 0.0), &w, &mut centered()).intensity, 0.0);
+        // in the cone but behind the spheres
+        assert_eq!(light.illuminate(Tuple::point(10.0, -10.0, 10.0), &w, &mut centered()).intensity, 0.0);
+    }
+}
@@ -1,42 +1,124 @@
-pub mod tuples;
 pub mod canon;
-pub mod colors;
-pub mod canvas;
 
-use ndarray::Array2;
-use ndarray_linalg::Array2;
+use std::env;
+use std::path::Path;
+use std::process;
+
+use ray::load_scene;
+
+const USAGE: &str = "usage: ray [--threads N] [--output FILE] [SCENE]
+
+  SCENE             a YAML scene file to render (default: the built-in
+                    three spheres)
+
+  -j, --threads N   render with N threads (default: all cores)
+  -o, --output FILE write the image to FILE, PPM if it ends in .ppm,
+                    PNG otherwise (default: foo.png)";
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    threads: usize,
+    output: String,
+    scene: Option<String>,
+    help: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        threads: 0,
+        output: "foo.png".to_string(),
+        scene: None,
+        help: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // both `--threads 4` and `--threads=4`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next().cloned()).ok_or(format!("{} needs a value", flag));
+        match flag {
+            "-j" | "--threads" => {
+                let v = value()?;
+                options.threads = v.parse().map_err(|_| format!("invalid thread count {:?}", v))?;
+            }
+            "-o" | "--output" => options.output = value()?,
+            "-h" | "--help" => options.help = true,
+            _ if !arg.starts_with('-') && options.scene.is_none() => options.scene = Some(arg.clone()),
+            _ => return Err(format!("unknown argument {:?}", arg)),
+        }
+    }
+    Ok(options)
+}
 
 fn main() {
-    /*
-    let x = 5;
-    let y = 5;
-    let mut test = Array2::<f64>::zeros((x, y));
-    test[[0,1]] = 2.0;
-    test[[1,1]] = 5.0;
-    test[[1,0]] = 3.0;
-    println!("{:?}", test);
-    
-
-    let mut test2 = Array2::<colors::Color>::default((x, y));
-    test2[[3,3]] = colors::Color::color(0.5, 0.5, 0.5);
-    println!("{:?}", test2);
-     */
-    //let c = canvas::Canvas::canvas(10, 20);
-    //println!("{:?}", c);
-    //println!("{:?}", c.to_ppm());
-    //canon::runme();
-
-    let mut test = Array2::<f64>::zeros((5, 5));
-    test[[0,1]] = 2.0;
-    test[[1,1]] = 5.0;
-    test[[1,0]] = 3.0;
-    println!("{:?}", test);
-    let mut test2 = test.clone().reversed_axes();
-    println!("{:?}", test2);
-    let mut test3 = Array2::<f64>::zeros((5, 1));
-    println!("{:?}", test3);
-    println!("{:?}", test.dot(&test2));
-    println!("{:?}", Array2::<f64>::eye(5));
-    // println!("{:?}", test);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    let result = match &options.scene {
+        Some(file) => match load_scene(Path::new(file)) {
+            Ok(scene) => canon::render(scene.camera, &scene.world, options.threads, &options.output),
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                process::exit(1);
+            }
+        },
+        None => canon::runme(options.threads, &options.output),
+    };
+    if let Err(e) = result {
+        eprintln!("{}: {}", options.output, e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_defaults() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.threads, 0);
+        assert_eq!(options.output, "foo.png");
+        assert_eq!(options.scene, None);
+        assert!(!options.help);
+    }
+
+    #[test]
+    fn test_threads() {
+        assert_eq!(parse(&["--threads", "4"]).unwrap().threads, 4);
+        assert_eq!(parse(&["--threads=3"]).unwrap().threads, 3);
+        assert_eq!(parse(&["-j", "2", "-o", "out.ppm"]).unwrap().threads, 2);
+        assert!(parse(&["--threads"]).is_err());
+        assert!(parse(&["-j", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_output_and_errors() {
+        assert_eq!(parse(&["-o", "out.ppm"]).unwrap().output, "out.ppm");
+        assert_eq!(parse(&["--output=a=b.png"]).unwrap().output, "a=b.png");
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["--frobnicate"]).is_err());
+    }
 
+    #[test]
+    fn test_scene() {
+        let options = parse(&["-j", "2", "scenes/spheres.yml"]).unwrap();
+        assert_eq!(options.scene.as_deref(), Some("scenes/spheres.yml"));
+        assert!(parse(&["a.yml", "b.yml"]).is_err());
+    }
 }
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use crate::colors::Color;
+use crate::patterns::Pattern;
+
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub color: Color,
+    /// When set, replaces `color`. Shared so materials stay cheap to clone.
+    pub pattern: Option<Arc<dyn Pattern>>,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
+}
+
+impl Material {
+    pub fn material() -> Material {
+        Material {
+            color: Color::color(1.0, 1.0, 1.0),
+            pattern: None,
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        }
+    }
+}
+
+// Patterns can't be compared, so materials only match when they share the
+// same one.
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_pattern
+            && self.color == other.color
+            && self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
+    }
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material::material()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::StripePattern;
+
+    #[test]
+    fn test_fn() {
+        let m = Material::material();
+        assert_eq!(m.color, Color::color(1.0, 1.0, 1.0));
+        assert!(m.pattern.is_none());
+        assert!(m.ambient == 0.1);
+        assert!(m.diffuse == 0.9);
+        assert!(m.specular == 0.9);
+        assert!(m.shininess == 200.0);
+        assert!(m.reflective == 0.0);
+        assert!(m.transparency == 0.0);
+        assert!(m.refractive_index == 1.0);
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut a = Material::material();
+        let mut b = Material::material();
+        assert_eq!(a, b);
+
+        let white = Color::color(1.0, 1.0, 1.0);
+        let p: Arc<dyn Pattern> = Arc::new(StripePattern::stripe_pattern(white, white));
+        a.pattern = Some(p.clone());
+        assert_ne!(a, b);
+        b.pattern = Some(p);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,321 @@
+use core::str::FromStr;
+use cucumber::Parameter;
+use ndarray::Array2;
+use std::ops::{Index, IndexMut, Mul};
+
+use crate::tuples::{equal, Tuple};
+
+#[derive(Debug, Clone, Parameter)]
+#[param(name = "matrix", regex = r"matrix\(.+\)")]
+pub struct Matrix {
+    pub size: usize,
+    pub data: Array2<f64>,
+}
+
+impl Matrix {
+    pub fn matrix(size: usize) -> Matrix {
+        Matrix {
+            size,
+            data: Array2::<f64>::zeros((size, size)),
+        }
+    }
+
+    pub fn identity() -> Matrix {
+        Matrix {
+            size: 4,
+            data: Array2::<f64>::eye(4),
+        }
+    }
+
+    /// Builds a square matrix from its rows. Panics if the rows do not form a square.
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Matrix {
+        let size = rows.len();
+        let mut m = Matrix::matrix(size);
+        for (r, row) in rows.iter().enumerate() {
+            assert_eq!(row.len(), size, "matrix rows must have {} columns", size);
+            for (c, v) in row.iter().enumerate() {
+                m.data[[r, c]] = *v;
+            }
+        }
+        m
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        Matrix {
+            size: self.size,
+            data: self.data.t().to_owned(),
+        }
+    }
+
+    pub fn submatrix(&self, row: usize, col: usize) -> Matrix {
+        let mut m = Matrix::matrix(self.size - 1);
+        for r in 0..self.size - 1 {
+            for c in 0..self.size - 1 {
+                let sr = if r < row { r } else { r + 1 };
+                let sc = if c < col { c } else { c + 1 };
+                m.data[[r, c]] = self.data[[sr, sc]];
+            }
+        }
+        m
+    }
+
+    pub fn minor(&self, row: usize, col: usize) -> f64 {
+        self.submatrix(row, col).determinant()
+    }
+
+    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
+        let minor = self.minor(row, col);
+        if (row + col) % 2 == 1 {
+            -minor
+        } else {
+            minor
+        }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        match self.size {
+            1 => self.data[[0, 0]],
+            2 => self.data[[0, 0]] * self.data[[1, 1]] - self.data[[0, 1]] * self.data[[1, 0]],
+            _ => (0..self.size)
+                .map(|c| self.data[[0, c]] * self.cofactor(0, c))
+                .sum(),
+        }
+    }
+
+    pub fn is_invertible(&self) -> bool {
+        self.determinant() != 0.0
+    }
+
+    /// Returns the inverse of the matrix, or `None` if its determinant is zero.
+    pub fn inverse(&self) -> Option<Matrix> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let mut m = Matrix::matrix(self.size);
+        for r in 0..self.size {
+            for c in 0..self.size {
+                // transposed on purpose: the inverse is the adjugate over the determinant
+                m.data[[c, r]] = self.cofactor(r, c) / det;
+            }
+        }
+        Some(m)
+    }
+}
+
+impl FromStr for Matrix {
+    type Err = String;
+    // Rows are separated by newlines or `;`, cells by `,` or `|`, so both
+    // `matrix(1, 2; 3, 4)` and gherkin-style `| 1 | 2 |` tables are accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = s.trim();
+        let body = body
+            .strip_prefix("matrix(")
+            .and_then(|b| b.strip_suffix(')'))
+            .unwrap_or(body);
+
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        for line in body.split(['\n', ';']) {
+            let cells = line
+                .split([',', '|'])
+                .map(|c| c.trim())
+                .filter(|c| !c.is_empty())
+                .map(|c| {
+                    c.parse::<f64>()
+                        .map_err(|e| format!("invalid matrix cell {:?}: {}", c, e))
+                })
+                .collect::<Result<Vec<f64>, String>>()?;
+            if !cells.is_empty() {
+                rows.push(cells);
+            }
+        }
+
+        if rows.is_empty() {
+            return Err(format!("empty matrix: {:?}", s));
+        }
+        if rows.iter().any(|r| r.len() != rows.len()) {
+            return Err(format!("matrix is not square: {:?}", s));
+        }
+        Ok(Matrix::from_rows(rows))
+    }
+}
+
+impl PartialEq for Matrix {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .all(|(a, b)| equal(*a, *b))
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+        &self.data[[row, col]]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+        &mut self.data[[row, col]]
+    }
+}
+
+impl Mul<&Matrix> for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: &Matrix) -> Matrix {
+        Matrix {
+            size: self.size,
+            data: self.data.dot(&other.data),
+        }
+    }
+}
+
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: Matrix) -> Matrix {
+        &self * &other
+    }
+}
+
+impl Mul<Tuple> for &Matrix {
+    type Output = Tuple;
+
+    fn mul(self, t: Tuple) -> Tuple {
+        let d = &self.data;
+        let row = |r: usize| d[[r, 0]] * t.x + d[[r, 1]] * t.y + d[[r, 2]] * t.z + d[[r, 3]] * t.w;
+        Tuple {
+            x: row(0),
+            y: row(1),
+            z: row(2),
+            w: row(3),
+        }
+    }
+}
+
+impl Mul<Tuple> for Matrix {
+    type Output = Tuple;
+
+    fn mul(self, t: Tuple) -> Tuple {
+        &self * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let m: Matrix = "matrix(1, 2, 3, 4; 5.5, 6.5, 7.5, 8.5; 9, 10, 11, 12; 13.5, 14.5, 15.5, 16.5)"
+            .parse()
+            .unwrap();
+        assert_eq!(m.size, 4);
+        assert!(m[(0, 0)] == 1.0);
+        assert!(m[(0, 3)] == 4.0);
+        assert!(m[(1, 0)] == 5.5);
+        assert!(m[(1, 2)] == 7.5);
+        assert!(m[(3, 2)] == 15.5);
+
+        let t: Matrix = "| -3 | 5 |\n| 1 | -2 |".parse().unwrap();
+        assert_eq!(t.size, 2);
+        assert!(t[(0, 0)] == -3.0);
+        assert!(t[(1, 1)] == -2.0);
+
+        assert!("matrix(1, 2; 3)".parse::<Matrix>().is_err());
+        assert!("matrix(1, x; 3, 4)".parse::<Matrix>().is_err());
+    }
+
+    #[test]
+    fn test_eq() {
+        let a: Matrix = "matrix(1, 2; 3, 4)".parse().unwrap();
+        let b: Matrix = "matrix(1, 2; 3, 4.000001)".parse().unwrap();
+        let c: Matrix = "matrix(2, 3; 4, 5)".parse().unwrap();
+        assert!(a == b);
+        assert!(a != c);
+    }
+
+    #[test]
+    fn test_mul() {
+        let a: Matrix = "matrix(1, 2, 3, 4; 5, 6, 7, 8; 9, 8, 7, 6; 5, 4, 3, 2)".parse().unwrap();
+        let b: Matrix = "matrix(-2, 1, 2, 3; 3, 2, 1, -1; 4, 3, 6, 5; 1, 2, 7, 8)".parse().unwrap();
+        let r: Matrix = "matrix(20, 22, 50, 48; 44, 54, 114, 108; 40, 58, 110, 102; 16, 26, 46, 42)"
+            .parse()
+            .unwrap();
+        assert_eq!(a * b, r);
+    }
+
+    #[test]
+    fn test_mul_tuple() {
+        let a: Matrix = "matrix(1, 2, 3, 4; 2, 4, 4, 2; 8, 6, 4, 1; 0, 0, 0, 1)".parse().unwrap();
+        let b = Tuple { x: 1.0, y: 2.0, z: 3.0, w: 1.0 };
+        assert_eq!(&a * b, Tuple { x: 18.0, y: 24.0, z: 33.0, w: 1.0 });
+        assert_eq!(&Matrix::identity() * b, b);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let a: Matrix = "matrix(0, 9, 3, 0; 9, 8, 0, 8; 1, 8, 5, 3; 0, 0, 5, 8)".parse().unwrap();
+        let t: Matrix = "matrix(0, 9, 1, 0; 9, 8, 8, 0; 3, 0, 5, 5; 0, 8, 3, 8)".parse().unwrap();
+        assert_eq!(a.transpose(), t);
+        assert_eq!(Matrix::identity().transpose(), Matrix::identity());
+    }
+
+    #[test]
+    fn test_submatrix() {
+        let a: Matrix = "matrix(1, 5, 0; -3, 2, 7; 0, 6, -3)".parse().unwrap();
+        let s: Matrix = "matrix(-3, 2; 0, 6)".parse().unwrap();
+        assert_eq!(a.submatrix(0, 2), s);
+    }
+
+    #[test]
+    fn test_minor_cofactor() {
+        let a: Matrix = "matrix(3, 5, 0; 2, -1, -7; 6, -1, 5)".parse().unwrap();
+        assert!(a.minor(0, 0) == -12.0);
+        assert!(a.cofactor(0, 0) == -12.0);
+        assert!(a.minor(1, 0) == 25.0);
+        assert!(a.cofactor(1, 0) == -25.0);
+    }
+
+    #[test]
+    fn test_determinant() {
+        let a: Matrix = "matrix(1, 5; -3, 2)".parse().unwrap();
+        assert!(a.determinant() == 17.0);
+
+        let b: Matrix = "matrix(-2, -8, 3, 5; -3, 1, 7, 3; 1, 2, -9, 6; -6, 7, 7, -9)".parse().unwrap();
+        assert!(b.cofactor(0, 0) == 690.0);
+        assert!(b.cofactor(0, 3) == 51.0);
+        assert!(b.determinant() == -4071.0);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let singular: Matrix = "matrix(-4, 2, -2, -3; 9, 6, 2, 6; 0, -5, 1, -5; 0, 0, 0, 0)".parse().unwrap();
+        assert!(!singular.is_invertible());
+        assert!(singular.inverse().is_none());
+
+        let a: Matrix = "matrix(-5, 2, 6, -8; 1, -5, 1, 8; 7, 7, -6, -7; 1, -3, 7, 4)".parse().unwrap();
+        let i: Matrix = "matrix(0.21805, 0.45113, 0.24060, -0.04511; -0.80827, -1.45677, -0.44361, 0.52068; -0.07895, -0.22368, -0.05263, 0.19737; -0.52256, -0.81391, -0.30075, 0.30639)"
+            .parse()
+            .unwrap();
+        let inv = a.inverse().unwrap();
+        assert!(inv[(3, 2)] == -160.0 / 532.0);
+        assert!(inv[(2, 3)] == 105.0 / 532.0);
+        // entries are given to 5 decimals
+        assert!(inv.data.iter().zip(i.data.iter()).all(|(x, y)| (x - y).abs() < 0.0001));
+    }
+
+    #[test]
+    fn test_inverse_product() {
+        let a: Matrix = "matrix(3, -9, 7, 3; 3, -8, 2, -9; -4, 4, 4, 1; -6, 5, -1, 1)".parse().unwrap();
+        let b: Matrix = "matrix(8, 2, 2, 2; 3, -1, 7, 0; 7, 0, 5, 4; 6, -2, 0, 5)".parse().unwrap();
+        let c = &a * &b;
+        assert_eq!(&c * &b.inverse().unwrap(), a);
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::OnceLock;
+
+// Ken Perlin's "improved noise": a fixed permutation of 0..256, repeated so
+// that lookups of `p[p[x] + y]` never need wrapping.
+fn permutation() -> &'static [usize; 512] {
+    static P: OnceLock<[usize; 512]> = OnceLock::new();
+    P.get_or_init(|| {
+        let mut p: Vec<usize> = (0..256).collect();
+        // any fixed shuffle will do; this one is a Fisher-Yates driven by an LCG
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for i in (1..256).rev() {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let j = (seed >> 33) as usize % (i + 1);
+            p.swap(i, j);
+        }
+        let mut table = [0; 512];
+        for (i, v) in table.iter_mut().enumerate() {
+            *v = p[i % 256];
+        }
+        table
+    })
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+// the dot product of (x, y, z) with one of 12 gradient directions
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+/// Smooth pseudo-random noise in roughly -1..1, zero at integer coordinates.
+pub fn perlin(x: f64, y: f64, z: f64) -> f64 {
+    let p = permutation();
+
+    let (xi, yi, zi) = (
+        x.floor().rem_euclid(256.0) as usize,
+        y.floor().rem_euclid(256.0) as usize,
+        z.floor().rem_euclid(256.0) as usize,
+    );
+    let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+
+    let a = p[xi] + yi;
+    let aa = p[a] + zi;
+    let ab = p[a + 1] + zi;
+    let b = p[xi + 1] + yi;
+    let ba = p[b] + zi;
+    let bb = p[b + 1] + zi;
+
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+            lerp(u, grad(p[ab], x, y - 1.0, z), grad(p[bb], x - 1.0, y - 1.0, z)),
+        ),
+        lerp(
+            v,
+            lerp(u, grad(p[aa + 1], x, y, z - 1.0), grad(p[ba + 1], x - 1.0, y, z - 1.0)),
+            lerp(u, grad(p[ab + 1], x, y - 1.0, z - 1.0), grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0)),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuples::equal;
+
+    #[test]
+    fn test_zero_on_lattice() {
+        assert!(equal(perlin(0.0, 0.0, 0.0), 0.0));
+        assert!(equal(perlin(3.0, -7.0, 12.0), 0.0));
+    }
+
+    #[test]
+    fn test_range_and_smoothness() {
+        let mut varies = false;
+        for i in 0..1000 {
+            let x = i as f64 * 0.137;
+            let n = perlin(x, x * 0.5, -x);
+            assert!((-1.5..=1.5).contains(&n));
+            // nearby points give nearby values
+            assert!((perlin(x + 0.001, x * 0.5, -x) - n).abs() < 0.01);
+            varies |= n.abs() > 0.1;
+        }
+        assert!(varies);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        assert!(perlin(1.3, 2.7, -0.4) == perlin(1.3, 2.7, -0.4));
+    }
+}
@@ -0,0 +1,330 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::groups::Group;
+use crate::triangles::{SmoothTriangle, Triangle};
+use crate::tuples::Tuple;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjErrorKind {
+    /// A value that should have been a number was not.
+    InvalidNumber(String),
+    /// A statement had fewer arguments than it needs.
+    MissingArguments,
+    /// A face referenced a vertex, normal or texture coordinate that does not exist.
+    InvalidIndex(String),
+}
+
+/// A malformed line in an OBJ file; `line` is 1-based.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjError {
+    pub line: usize,
+    pub kind: ObjErrorKind,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ObjErrorKind::InvalidNumber(v) => write!(f, "line {}: invalid number {:?}", self.line, v),
+            ObjErrorKind::MissingArguments => write!(f, "line {}: missing arguments", self.line),
+            ObjErrorKind::InvalidIndex(v) => write!(f, "line {}: invalid index {:?}", self.line, v),
+        }
+    }
+}
+
+impl Error for ObjError {}
+
+/// The result of parsing an OBJ file. Faces outside of any `g` statement end
+/// up in `default_group`, the others in the matching entry of `groups`.
+#[derive(Debug, Default)]
+pub struct ObjFile {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub textures: Vec<(f64, f64)>,
+    pub default_group: Group,
+    pub groups: Vec<(String, Group)>,
+    /// Non-blank lines that were not understood, comments included.
+    pub ignored: usize,
+}
+
+impl ObjFile {
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|(n, _)| n == name).map(|(_, g)| g)
+    }
+
+    /// Collects every face into one group, with each named group as a child.
+    pub fn to_group(self) -> Group {
+        let mut g = self.default_group;
+        for (_, child) in self.groups {
+            g.add_child(Box::new(child));
+        }
+        g
+    }
+}
+
+// one `v/vt/vn` reference of a face, resolved to 0-based indices
+struct FaceVertex {
+    vertex: usize,
+    normal: Option<usize>,
+}
+
+fn parse_number(s: &str, line: usize) -> Result<f64, ObjError> {
+    s.parse::<f64>().map_err(|_| ObjError {
+        line,
+        kind: ObjErrorKind::InvalidNumber(s.to_string()),
+    })
+}
+
+fn parse_numbers(args: &[&str], count: usize, line: usize) -> Result<Vec<f64>, ObjError> {
+    if args.len() < count {
+        return Err(ObjError {
+            line,
+            kind: ObjErrorKind::MissingArguments,
+        });
+    }
+    args[..count].iter().map(|a| parse_number(a, line)).collect()
+}
+
+// OBJ indices are 1-based; negative ones count back from the latest element.
+fn resolve_index(s: &str, len: usize, line: usize) -> Result<usize, ObjError> {
+    let invalid = || ObjError {
+        line,
+        kind: ObjErrorKind::InvalidIndex(s.to_string()),
+    };
+    let i = s.parse::<i64>().map_err(|_| invalid())?;
+    let idx = if i > 0 { i - 1 } else { len as i64 + i };
+    if i == 0 || idx < 0 || idx >= len as i64 {
+        return Err(invalid());
+    }
+    Ok(idx as usize)
+}
+
+fn parse_face_vertex(s: &str, obj: &ObjFile, line: usize) -> Result<FaceVertex, ObjError> {
+    let mut parts = s.split('/');
+    let vertex = resolve_index(parts.next().unwrap_or(""), obj.vertices.len(), line)?;
+    // texture coordinates are not used for triangles, so files without any
+    // `vt` lines may put whatever they like there, as the book's examples do
+    if let Some(vt) = parts.next() {
+        if !vt.is_empty() && !obj.textures.is_empty() {
+            resolve_index(vt, obj.textures.len(), line)?;
+        }
+    }
+    let normal = match parts.next() {
+        Some(vn) if !vn.is_empty() => Some(resolve_index(vn, obj.normals.len(), line)?),
+        _ => None,
+    };
+    Ok(FaceVertex { vertex, normal })
+}
+
+pub fn parse_obj_file(input: &str) -> Result<ObjFile, ObjError> {
+    let mut obj = ObjFile::default();
+    // index into `obj.groups` of the group faces currently go to
+    let mut current: Option<usize> = None;
+
+    for (i, text) in input.lines().enumerate() {
+        let line = i + 1;
+        let mut words = text.split_whitespace();
+        let directive = match words.next() {
+            Some(d) => d,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+
+        match directive {
+            "v" => {
+                let n = parse_numbers(&args, 3, line)?;
+                obj.vertices.push(Tuple::point(n[0], n[1], n[2]));
+            }
+            "vn" => {
+                let n = parse_numbers(&args, 3, line)?;
+                obj.normals.push(Tuple::vector(n[0], n[1], n[2]));
+            }
+            "vt" => {
+                let n = parse_numbers(&args, 1, line)?;
+                let v = match args.get(1) {
+                    Some(a) => parse_number(a, line)?,
+                    None => 0.0,
+                };
+                obj.textures.push((n[0], v));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(ObjError {
+                        line,
+                        kind: ObjErrorKind::MissingArguments,
+                    });
+                }
+                let face = args
+                    .iter()
+                    .map(|a| parse_face_vertex(a, &obj, line))
+                    .collect::<Result<Vec<FaceVertex>, ObjError>>()?;
+
+                let group = match current {
+                    Some(idx) => &mut obj.groups[idx].1,
+                    None => &mut obj.default_group,
+                };
+                // fan triangulation around the first vertex
+                for j in 1..face.len() - 1 {
+                    let (a, b, c) = (&face[0], &face[j], &face[j + 1]);
+                    let (p1, p2, p3) = (obj.vertices[a.vertex], obj.vertices[b.vertex], obj.vertices[c.vertex]);
+                    match (a.normal, b.normal, c.normal) {
+                        (Some(n1), Some(n2), Some(n3)) => group.add_child(Box::new(SmoothTriangle::smooth_triangle(
+                            p1,
+                            p2,
+                            p3,
+                            obj.normals[n1],
+                            obj.normals[n2],
+                            obj.normals[n3],
+                        ))),
+                        _ => group.add_child(Box::new(Triangle::triangle(p1, p2, p3))),
+                    }
+                }
+            }
+            "g" => {
+                let name = args.join(" ");
+                current = match obj.groups.iter().position(|(n, _)| *n == name) {
+                    Some(idx) => Some(idx),
+                    None => {
+                        obj.groups.push((name, Group::group()));
+                        Some(obj.groups.len() - 1)
+                    }
+                };
+            }
+            _ => obj.ignored += 1,
+        }
+    }
+
+    Ok(obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Shape;
+    use std::any::Any;
+
+    fn triangle(shape: &dyn Shape) -> &Triangle {
+        (shape as &dyn Any).downcast_ref().expect("a triangle")
+    }
+
+    fn smooth_triangle(shape: &dyn Shape) -> &SmoothTriangle {
+        (shape as &dyn Any).downcast_ref().expect("a smooth triangle")
+    }
+
+    fn assert_triangle(shape: &dyn Shape, p1: Tuple, p2: Tuple, p3: Tuple) {
+        let t = triangle(shape);
+        assert_eq!((t.p1, t.p2, t.p3), (p1, p2, p3));
+    }
+
+    #[test]
+    fn test_ignores_gibberish() {
+        let obj = parse_obj_file(
+            "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+
+in a relative way,
+and came back the previous night.",
+        )
+        .unwrap();
+        assert_eq!(obj.ignored, 5);
+    }
+
+    #[test]
+    fn test_vertices() {
+        let obj = parse_obj_file("v -1 1 0\nv -1.0000 0.5000 0.0000\nv 1 0 0\nv 1 1 0").unwrap();
+        assert_eq!(obj.vertices[0], Tuple::point(-1.0, 1.0, 0.0));
+        assert_eq!(obj.vertices[1], Tuple::point(-1.0, 0.5, 0.0));
+        assert_eq!(obj.vertices[2], Tuple::point(1.0, 0.0, 0.0));
+        assert_eq!(obj.vertices[3], Tuple::point(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_triangle_faces() {
+        let obj = parse_obj_file("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\nf 1 2 3\nf 1 3 4").unwrap();
+        let g = &obj.default_group;
+        assert_eq!(g.len(), 2);
+        let v = &obj.vertices;
+        assert_triangle(g.children()[0].as_ref(), v[0], v[1], v[2]);
+        assert_triangle(g.children()[1].as_ref(), v[0], v[2], v[3]);
+    }
+
+    #[test]
+    fn test_polygon_fan() {
+        let obj = parse_obj_file("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\nv 0 2 0\n\nf 1 2 3 4 5").unwrap();
+        let g = &obj.default_group;
+        assert_eq!(g.len(), 3);
+        let v = &obj.vertices;
+        assert_triangle(g.children()[0].as_ref(), v[0], v[1], v[2]);
+        assert_triangle(g.children()[1].as_ref(), v[0], v[2], v[3]);
+        assert_triangle(g.children()[2].as_ref(), v[0], v[3], v[4]);
+    }
+
+    #[test]
+    fn test_named_groups() {
+        let obj = parse_obj_file("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\ng FirstGroup\nf 1 2 3\ng SecondGroup\nf 1 3 4")
+            .unwrap();
+        let v = &obj.vertices;
+        let g1 = obj.group("FirstGroup").unwrap();
+        let g2 = obj.group("SecondGroup").unwrap();
+        assert_triangle(g1.children()[0].as_ref(), v[0], v[1], v[2]);
+        assert_triangle(g2.children()[0].as_ref(), v[0], v[2], v[3]);
+        assert!(obj.default_group.is_empty());
+
+        let g = obj.to_group();
+        assert_eq!(g.len(), 2);
+    }
+
+    #[test]
+    fn test_vertex_normals() {
+        let obj = parse_obj_file("vn 0 0 1\nvn 0.707 0 -0.707\nvn 1 2 3").unwrap();
+        assert_eq!(obj.normals[0], Tuple::vector(0.0, 0.0, 1.0));
+        assert_eq!(obj.normals[1], Tuple::vector(0.707, 0.0, -0.707));
+        assert_eq!(obj.normals[2], Tuple::vector(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_faces_with_normals() {
+        let obj = parse_obj_file("v 0 1 0\nv -1 0 0\nv 1 0 0\n\nvn -1 0 0\nvn 1 0 0\nvn 0 1 0\n\nf 1//3 2//1 3//2\nf 1/0/3 2/102/1 3/14/2")
+            .unwrap();
+        let (v, n) = (&obj.vertices, &obj.normals);
+        for child in obj.default_group.children() {
+            let t = smooth_triangle(child.as_ref());
+            assert_eq!((t.p1, t.p2, t.p3), (v[0], v[1], v[2]));
+            assert_eq!((t.n1, t.n2, t.n3), (n[2], n[0], n[1]));
+        }
+
+        // once the file has texture coordinates, references to them must exist
+        let obj = parse_obj_file("v 0 1 0\nv -1 0 0\nv 1 0 0\nvn 0 1 0\nvt 0 0\nvt 1 1\nf 1/1/1 2/2/1 3/1/1")
+            .unwrap();
+        assert_eq!(obj.textures, vec![(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(obj.default_group.len(), 1);
+        let err = parse_obj_file("v 0 1 0\nv -1 0 0\nv 1 0 0\nvt 0 0\nf 1/1 2/2 3/1").unwrap_err();
+        assert_eq!(err, ObjError { line: 5, kind: ObjErrorKind::InvalidIndex("2".to_string()) });
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let obj = parse_obj_file("v -1 1 0\nv -1 0 0\nv 1 0 0\nf -3 -2 -1").unwrap();
+        let v = &obj.vertices;
+        assert_triangle(obj.default_group.children()[0].as_ref(), v[0], v[1], v[2]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse_obj_file("v 1 2 3\nv 1 x 3").unwrap_err();
+        assert_eq!(err, ObjError { line: 2, kind: ObjErrorKind::InvalidNumber("x".to_string()) });
+
+        let err = parse_obj_file("# comment\nvn 1 2").unwrap_err();
+        assert_eq!(err, ObjError { line: 2, kind: ObjErrorKind::MissingArguments });
+
+        let err = parse_obj_file("v 1 2 3\nv 1 2 4\nf 1 2").unwrap_err();
+        assert_eq!(err, ObjError { line: 3, kind: ObjErrorKind::MissingArguments });
+
+        let err = parse_obj_file("v 1 2 3\nv 1 2 4\nv 1 2 5\nf 1 2 4").unwrap_err();
+        assert_eq!(err, ObjError { line: 4, kind: ObjErrorKind::InvalidIndex("4".to_string()) });
+
+        let err = parse_obj_file("v 1 2 3\nv 1 2 4\nv 1 2 5\nf 0 1 2").unwrap_err();
+        assert_eq!(err.to_string(), "line 4: invalid index \"0\"");
+    }
+}
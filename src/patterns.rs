@@ -0,0 +1,563 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::colors::Color;
+use crate::matrices::Matrix;
+use crate::noise::perlin;
+use crate::shapes::Shape;
+use crate::tuples::Tuple;
+
+/// The transform every pattern carries, with its cached inverse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternProps {
+    transform: Matrix,
+    inverse: Matrix,
+}
+
+impl PatternProps {
+    pub fn pattern_props() -> PatternProps {
+        PatternProps {
+            transform: Matrix::identity(),
+            inverse: Matrix::identity(),
+        }
+    }
+
+    /// Sets the transform, caching its inverse. Panics if the matrix is not invertible.
+    pub fn set_transform(&mut self, m: Matrix) {
+        self.inverse = m.inverse().expect("pattern transform must be invertible");
+        self.transform = m;
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.transform
+    }
+
+    pub fn inverse(&self) -> &Matrix {
+        &self.inverse
+    }
+}
+
+impl Default for PatternProps {
+    fn default() -> PatternProps {
+        PatternProps::pattern_props()
+    }
+}
+
+/// A color that varies over space, used by `Material` instead of a flat color.
+///
+/// Like shapes, implementors only deal with their own space through
+/// `local_pattern_at`; `pattern_at_shape` takes care of moving a world space
+/// point through the object's transform and then the pattern's.
+pub trait Pattern: Debug + Send + Sync {
+    fn props(&self) -> &PatternProps;
+
+    fn props_mut(&mut self) -> &mut PatternProps;
+
+    /// The color at a point given in pattern space.
+    fn local_pattern_at(&self, point: Tuple) -> Color;
+
+    fn transform(&self) -> &Matrix {
+        self.props().transform()
+    }
+
+    fn set_transform(&mut self, m: Matrix) {
+        self.props_mut().set_transform(m);
+    }
+
+    /// The color at a point given in the space of whatever the pattern is
+    /// applied to: a shape, or the pattern enclosing this one.
+    fn pattern_at(&self, point: Tuple) -> Color {
+        self.local_pattern_at(self.props().inverse() * point)
+    }
+
+    fn pattern_at_shape(&self, object: &dyn Shape, world_point: Tuple) -> Color {
+        self.pattern_at(object.world_to_object(world_point))
+    }
+}
+
+/// A single color everywhere; the leaves of nested patterns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolidPattern {
+    pub props: PatternProps,
+    pub color: Color,
+}
+
+impl SolidPattern {
+    pub fn solid_pattern(color: Color) -> SolidPattern {
+        SolidPattern {
+            props: PatternProps::pattern_props(),
+            color,
+        }
+    }
+}
+
+impl Pattern for SolidPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, _point: Tuple) -> Color {
+        self.color
+    }
+}
+
+fn solid(color: Color) -> Arc<dyn Pattern> {
+    Arc::new(SolidPattern::solid_pattern(color))
+}
+
+/// Alternates between `a` and `b` every unit along x.
+#[derive(Debug)]
+pub struct StripePattern {
+    pub props: PatternProps,
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+}
+
+impl StripePattern {
+    pub fn stripe_pattern(a: Color, b: Color) -> StripePattern {
+        StripePattern::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Arc<dyn Pattern>, b: Arc<dyn Pattern>) -> StripePattern {
+        StripePattern {
+            props: PatternProps::pattern_props(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for StripePattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        if point.x.floor().rem_euclid(2.0) == 0.0 {
+            self.a.pattern_at(point)
+        } else {
+            self.b.pattern_at(point)
+        }
+    }
+}
+
+/// Blends linearly from `a` at x = 0 to `b` at x = 1, repeating every unit.
+#[derive(Debug)]
+pub struct GradientPattern {
+    pub props: PatternProps,
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+}
+
+impl GradientPattern {
+    pub fn gradient_pattern(a: Color, b: Color) -> GradientPattern {
+        GradientPattern::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Arc<dyn Pattern>, b: Arc<dyn Pattern>) -> GradientPattern {
+        GradientPattern {
+            props: PatternProps::pattern_props(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for GradientPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        let a = self.a.pattern_at(point);
+        let b = self.b.pattern_at(point);
+        a + (b - a) * (point.x - point.x.floor())
+    }
+}
+
+/// Blends from `a` to `b` with the distance from the y axis, repeating
+/// every unit: a gradient version of `RingPattern`.
+#[derive(Debug)]
+pub struct RadialGradientPattern {
+    pub props: PatternProps,
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+}
+
+impl RadialGradientPattern {
+    pub fn radial_gradient_pattern(a: Color, b: Color) -> RadialGradientPattern {
+        RadialGradientPattern::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Arc<dyn Pattern>, b: Arc<dyn Pattern>) -> RadialGradientPattern {
+        RadialGradientPattern {
+            props: PatternProps::pattern_props(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for RadialGradientPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        let distance = (point.x.powi(2) + point.z.powi(2)).sqrt();
+        let a = self.a.pattern_at(point);
+        let b = self.b.pattern_at(point);
+        a + (b - a) * (distance - distance.floor())
+    }
+}
+
+/// Concentric rings around the y axis, alternating every unit of distance.
+#[derive(Debug)]
+pub struct RingPattern {
+    pub props: PatternProps,
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+}
+
+impl RingPattern {
+    pub fn ring_pattern(a: Color, b: Color) -> RingPattern {
+        RingPattern::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Arc<dyn Pattern>, b: Arc<dyn Pattern>) -> RingPattern {
+        RingPattern {
+            props: PatternProps::pattern_props(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for RingPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        let distance = (point.x.powi(2) + point.z.powi(2)).sqrt();
+        if distance.floor().rem_euclid(2.0) == 0.0 {
+            self.a.pattern_at(point)
+        } else {
+            self.b.pattern_at(point)
+        }
+    }
+}
+
+/// Unit cubes alternating between `a` and `b` in all three dimensions.
+#[derive(Debug)]
+pub struct CheckersPattern {
+    pub props: PatternProps,
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+}
+
+impl CheckersPattern {
+    pub fn checkers_pattern(a: Color, b: Color) -> CheckersPattern {
+        CheckersPattern::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Arc<dyn Pattern>, b: Arc<dyn Pattern>) -> CheckersPattern {
+        CheckersPattern {
+            props: PatternProps::pattern_props(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for CheckersPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        let sum = point.x.floor() + point.y.floor() + point.z.floor();
+        if sum.rem_euclid(2.0) == 0.0 {
+            self.a.pattern_at(point)
+        } else {
+            self.b.pattern_at(point)
+        }
+    }
+}
+
+/// The average of two patterns.
+#[derive(Debug)]
+pub struct BlendedPattern {
+    pub props: PatternProps,
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+}
+
+impl BlendedPattern {
+    pub fn blended_pattern(a: Arc<dyn Pattern>, b: Arc<dyn Pattern>) -> BlendedPattern {
+        BlendedPattern {
+            props: PatternProps::pattern_props(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for BlendedPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        (self.a.pattern_at(point) + self.b.pattern_at(point)) * 0.5
+    }
+}
+
+/// Another pattern, sampled at points jittered by Perlin noise so its
+/// edges look organic. `scale` is how far a point may be moved.
+#[derive(Debug)]
+pub struct PerturbedPattern {
+    pub props: PatternProps,
+    pub pattern: Arc<dyn Pattern>,
+    pub scale: f64,
+}
+
+impl PerturbedPattern {
+    pub fn perturbed_pattern(pattern: Arc<dyn Pattern>, scale: f64) -> PerturbedPattern {
+        PerturbedPattern {
+            props: PatternProps::pattern_props(),
+            pattern,
+            scale,
+        }
+    }
+}
+
+impl Pattern for PerturbedPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        // offset z for the other two axes so they don't all move in lockstep
+        let (x, y, z) = (point.x, point.y, point.z);
+        let jitter = Tuple::vector(perlin(x, y, z), perlin(x, y, z + 1.0), perlin(x, y, z + 2.0));
+        self.pattern.pattern_at(point + jitter * self.scale)
+    }
+}
+
+/// Colors each point with its own coordinates, to check which space a
+/// pattern is evaluated in.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct TestPattern {
+    pub props: PatternProps,
+}
+
+#[cfg(test)]
+impl Pattern for TestPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        Color::color(point.x, point.y, point.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::groups::Group;
+    use crate::spheres::Sphere;
+    use crate::transformations::{rotation_y, scaling, translation};
+    use std::f64::consts::FRAC_PI_2;
+
+    fn white() -> Color {
+        Color::color(1.0, 1.0, 1.0)
+    }
+
+    fn black() -> Color {
+        Color::color(0.0, 0.0, 0.0)
+    }
+
+    fn at(p: &dyn Pattern, x: f64, y: f64, z: f64) -> Color {
+        p.pattern_at(Tuple::point(x, y, z))
+    }
+
+    #[test]
+    fn test_default_transform() {
+        let p = TestPattern::default();
+        assert_eq!(*p.transform(), Matrix::identity());
+        let mut p = TestPattern::default();
+        p.set_transform(translation(1.0, 2.0, 3.0));
+        assert_eq!(*p.transform(), translation(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_object_transform() {
+        let mut s = Sphere::sphere();
+        s.set_transform(scaling(2.0, 2.0, 2.0));
+        let c = TestPattern::default().pattern_at_shape(&s, Tuple::point(2.0, 3.0, 4.0));
+        assert_eq!(c, Color::color(1.0, 1.5, 2.0));
+    }
+
+    #[test]
+    fn test_pattern_transform() {
+        let s = Sphere::sphere();
+        let mut p = TestPattern::default();
+        p.set_transform(scaling(2.0, 2.0, 2.0));
+        assert_eq!(p.pattern_at_shape(&s, Tuple::point(2.0, 3.0, 4.0)), Color::color(1.0, 1.5, 2.0));
+    }
+
+    #[test]
+    fn test_object_and_pattern_transform() {
+        let mut s = Sphere::sphere();
+        s.set_transform(scaling(2.0, 2.0, 2.0));
+        let mut p = TestPattern::default();
+        p.set_transform(translation(0.5, 1.0, 1.5));
+        assert_eq!(p.pattern_at_shape(&s, Tuple::point(2.5, 3.0, 3.5)), Color::color(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn test_shape_in_group() {
+        let mut g = Group::group();
+        g.set_transform(scaling(2.0, 2.0, 2.0));
+        let mut s = Sphere::sphere();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        g.add_child(Box::new(s));
+        let c = TestPattern::default().pattern_at_shape(g.children()[0].as_ref(), Tuple::point(12.0, 2.0, 4.0));
+        assert_eq!(c, Color::color(1.0, 1.0, 2.0));
+    }
+
+    #[test]
+    fn test_stripe() {
+        let p = StripePattern::stripe_pattern(white(), black());
+        // constant in y and z
+        assert_eq!(at(&p, 0.0, 1.0, 0.0), white());
+        assert_eq!(at(&p, 0.0, 2.0, 0.0), white());
+        assert_eq!(at(&p, 0.0, 0.0, 2.0), white());
+        // alternates in x
+        assert_eq!(at(&p, 0.9, 0.0, 0.0), white());
+        assert_eq!(at(&p, 1.0, 0.0, 0.0), black());
+        assert_eq!(at(&p, -0.1, 0.0, 0.0), black());
+        assert_eq!(at(&p, -1.0, 0.0, 0.0), black());
+        assert_eq!(at(&p, -1.1, 0.0, 0.0), white());
+    }
+
+    #[test]
+    fn test_gradient() {
+        let p = GradientPattern::gradient_pattern(white(), black());
+        assert_eq!(at(&p, 0.0, 0.0, 0.0), white());
+        assert_eq!(at(&p, 0.25, 0.0, 0.0), Color::color(0.75, 0.75, 0.75));
+        assert_eq!(at(&p, 0.5, 0.0, 0.0), Color::color(0.5, 0.5, 0.5));
+        assert_eq!(at(&p, 0.75, 0.0, 0.0), Color::color(0.25, 0.25, 0.25));
+    }
+
+    #[test]
+    fn test_radial_gradient() {
+        let p = RadialGradientPattern::radial_gradient_pattern(white(), black());
+        assert_eq!(at(&p, 0.0, 0.0, 0.0), white());
+        assert_eq!(at(&p, 0.25, 0.0, 0.0), Color::color(0.75, 0.75, 0.75));
+        assert_eq!(at(&p, 0.0, 5.0, 0.5), Color::color(0.5, 0.5, 0.5));
+        assert_eq!(at(&p, 3.0, 0.0, 4.0), white());
+    }
+
+    #[test]
+    fn test_ring() {
+        let p = RingPattern::ring_pattern(white(), black());
+        assert_eq!(at(&p, 0.0, 0.0, 0.0), white());
+        assert_eq!(at(&p, 1.0, 0.0, 0.0), black());
+        assert_eq!(at(&p, 0.0, 0.0, 1.0), black());
+        // 0.708 = just slightly more than sqrt(2)/2
+        assert_eq!(at(&p, 0.708, 0.0, 0.708), black());
+    }
+
+    #[test]
+    fn test_checkers() {
+        let p = CheckersPattern::checkers_pattern(white(), black());
+        assert_eq!(at(&p, 0.0, 0.0, 0.0), white());
+        assert_eq!(at(&p, 0.99, 0.0, 0.0), white());
+        assert_eq!(at(&p, 1.01, 0.0, 0.0), black());
+        assert_eq!(at(&p, 0.0, 0.99, 0.0), white());
+        assert_eq!(at(&p, 0.0, 1.01, 0.0), black());
+        assert_eq!(at(&p, 0.0, 0.0, 0.99), white());
+        assert_eq!(at(&p, 0.0, 0.0, 1.01), black());
+    }
+
+    #[test]
+    fn test_nested() {
+        // stripes of checkers on one side and solid black on the other
+        let mut checkers = CheckersPattern::checkers_pattern(white(), Color::color(1.0, 0.0, 0.0));
+        checkers.set_transform(scaling(0.5, 0.5, 0.5));
+        let p = StripePattern::nested(Arc::new(checkers), solid(black()));
+        assert_eq!(at(&p, 0.25, 0.25, 0.25), white());
+        assert_eq!(at(&p, 0.75, 0.25, 0.25), Color::color(1.0, 0.0, 0.0));
+        assert_eq!(at(&p, 1.25, 0.25, 0.25), black());
+    }
+
+    #[test]
+    fn test_blended() {
+        let a = StripePattern::stripe_pattern(white(), black());
+        let mut b = StripePattern::stripe_pattern(white(), black());
+        // stripes along z instead, white for -1 < z <= 0
+        b.set_transform(rotation_y(FRAC_PI_2));
+        let p = BlendedPattern::blended_pattern(Arc::new(a), Arc::new(b));
+        assert_eq!(at(&p, 0.5, 0.0, -0.5), white());
+        assert_eq!(at(&p, 1.5, 0.0, -0.5), Color::color(0.5, 0.5, 0.5));
+        assert_eq!(at(&p, 1.5, 0.0, 0.5), black());
+    }
+
+    #[test]
+    fn test_perturbed() {
+        let stripes: Arc<dyn Pattern> = Arc::new(StripePattern::stripe_pattern(white(), black()));
+        // noise is zero on lattice points, so those are left alone
+        let p = PerturbedPattern::perturbed_pattern(stripes.clone(), 0.5);
+        assert_eq!(at(&p, 0.0, 0.0, 0.0), white());
+
+        // elsewhere some points near a stripe edge get moved across it
+        let moved = (0..100)
+            .map(|i| Tuple::point(0.95, i as f64 * 0.37 + 0.1, i as f64 * 0.11 + 0.2))
+            .filter(|pt| p.pattern_at(*pt) != stripes.pattern_at(*pt))
+            .count();
+        assert!(moved > 0);
+
+        let still = PerturbedPattern::perturbed_pattern(stripes.clone(), 0.0);
+        assert_eq!(at(&still, 0.95, 0.3, 0.7), white());
+    }
+}
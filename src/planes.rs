@@ -0,0 +1,101 @@
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::rays::Ray;
+use crate::shapes::{Shape, ShapeProps};
+use crate::tuples::{Tuple, EPSILON};
+
+/// The infinite xz plane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plane {
+    pub props: ShapeProps,
+}
+
+impl Plane {
+    pub fn plane() -> Plane {
+        Plane {
+            props: ShapeProps::shape_props(),
+        }
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Plane {
+        Plane::plane()
+    }
+}
+
+impl Shape for Plane {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        // a ray parallel to (or coplanar with) the plane never hits it
+        if ray.direction.y.abs() < EPSILON {
+            return Intersections::default();
+        }
+        let t = -ray.origin.y / ray.direction.y;
+        Intersections::intersections(vec![Intersection::intersection(t, self)])
+    }
+
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        Tuple::vector(0.0, 1.0, 0.0)
+    }
+
+    /// Infinite in x and z, flat in y.
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::bounding_box(
+            Tuple::point(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Tuple::point(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normal_is_constant() {
+        let p = Plane::plane();
+        let up = Tuple::vector(0.0, 1.0, 0.0);
+        assert_eq!(p.local_normal_at(Tuple::point(0.0, 0.0, 0.0)), up);
+        assert_eq!(p.local_normal_at(Tuple::point(10.0, 0.0, -10.0)), up);
+        assert_eq!(p.local_normal_at(Tuple::point(-5.0, 0.0, 150.0)), up);
+    }
+
+    #[test]
+    fn test_intersect_parallel() {
+        let p = Plane::plane();
+        let r = Ray::ray(Tuple::point(0.0, 10.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(p.local_intersect(&r).is_empty());
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(p.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn test_intersect_above_below() {
+        let p = Plane::plane();
+        let r = Ray::ray(Tuple::point(0.0, 1.0, 0.0), Tuple::vector(0.0, -1.0, 0.0));
+        let xs = p.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].t == 1.0);
+        assert!(std::ptr::addr_eq(xs[0].object, &p));
+
+        let r = Ray::ray(Tuple::point(0.0, -1.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = p.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].t == 1.0);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = Plane::plane().bounds();
+        assert!(b.min.x == f64::NEG_INFINITY && b.min.y == 0.0 && b.min.z == f64::NEG_INFINITY);
+        assert!(b.max.x == f64::INFINITY && b.max.y == 0.0 && b.max.z == f64::INFINITY);
+    }
+}
@@ -0,0 +1,356 @@
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+use crate::canvas::Canvas;
+use crate::colors::{Encoding, Quantizer};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// the largest payload of a stored (uncompressed) deflate block
+const MAX_BLOCK: usize = 65535;
+
+fn crc_table() -> &'static [u32; 256] {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+            *entry = c;
+        }
+        table
+    })
+}
+
+fn update_crc(crc: u32, data: &[u8]) -> u32 {
+    let table = crc_table();
+    data.iter().fold(crc, |c, b| table[((c ^ *b as u32) & 0xff) as usize] ^ (c >> 8))
+}
+
+/// The CRC-32 used by PNG chunks (and zip, gzip, ...).
+pub fn crc32(data: &[u8]) -> u32 {
+    !update_crc(!0, data)
+}
+
+/// Running Adler-32 checksum, the trailer of a zlib stream.
+#[derive(Debug, Clone, Copy)]
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    fn adler32() -> Adler32 {
+        Adler32 { a: 1, b: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        // 5552 bytes is the most that can be summed before `b` could overflow
+        for chunk in data.chunks(5552) {
+            for byte in chunk {
+                self.a += *byte as u32;
+                self.b += self.a;
+            }
+            self.a %= 65521;
+            self.b %= 65521;
+        }
+    }
+
+    fn value(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    // the CRC covers the type and the data, but not the length
+    let crc = !update_crc(update_crc(!0, kind), data);
+    w.write_all(&crc.to_be_bytes())
+}
+
+// Writes the zlib stream of the image data as a series of IDAT chunks, one
+// stored deflate block per chunk, so that only a block's worth of pixels is
+// buffered at a time.
+struct IdatWriter<W: Write> {
+    w: W,
+    pending: Vec<u8>,
+    remaining: usize,
+    adler: Adler32,
+    started: bool,
+}
+
+impl<W: Write> IdatWriter<W> {
+    fn push(&mut self, data: &[u8]) -> io::Result<()> {
+        self.pending.extend_from_slice(data);
+        while self.pending.len() >= MAX_BLOCK {
+            self.write_block(MAX_BLOCK)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        // an empty image still needs its one, final, block
+        if !self.pending.is_empty() || !self.started {
+            self.write_block(self.pending.len())?;
+        }
+        Ok(self.w)
+    }
+
+    fn write_block(&mut self, len: usize) -> io::Result<()> {
+        let block: Vec<u8> = self.pending.drain(..len).collect();
+        self.remaining -= len;
+        let last = self.remaining == 0;
+
+        let mut data = Vec::with_capacity(len + 11);
+        if !self.started {
+            // deflate with a 32K window, no preset dictionary; the header
+            // checksum makes 0x7801 a multiple of 31
+            data.extend_from_slice(&[0x78, 0x01]);
+            self.started = true;
+        }
+        data.push(last as u8);
+        data.extend_from_slice(&(len as u16).to_le_bytes());
+        data.extend_from_slice(&(!(len as u16)).to_le_bytes());
+        data.extend_from_slice(&block);
+        self.adler.update(&block);
+        if last {
+            data.extend_from_slice(&self.adler.value().to_be_bytes());
+        }
+        write_chunk(&mut self.w, b"IDAT", &data)
+    }
+}
+
+impl Canvas {
+    /// Writes the canvas as an RGB PNG image, 8 bit when the quantizer's
+    /// maximum value is 255 and 16 bit when it is 65535. The image data is
+    /// stored uncompressed, which every decoder accepts, and is streamed row
+    /// by row. PNG has no empty images, so a canvas with a zero side is an
+    /// `InvalidInput` error.
+    pub fn write_png<W: Write>(&self, mut w: W, quantizer: &Quantizer) -> io::Result<()> {
+        let depth = match quantizer.max_value {
+            255 => 8,
+            65535 => 16,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "PNG maximum value must be 255 or 65535")),
+        };
+        // the format allows 1 to 2^31 - 1 pixels along each side
+        const MAX_SIDE: usize = i32::MAX as usize;
+        if self.width > MAX_SIDE || self.height > MAX_SIDE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "canvas too large for PNG"));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty canvas can't be written as PNG"));
+        }
+        w.write_all(&SIGNATURE)?;
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&(self.width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // color type 2 (RGB), deflate, no filtering, no interlacing
+        ihdr.extend_from_slice(&[depth, 2, 0, 0, 0]);
+        write_chunk(&mut w, b"IHDR", &ihdr)?;
+
+        // tell viewers how the values were encoded; unencoded values are left
+        // untagged so they show as they would in a PPM, while `Gamma(1.0)`
+        // asks for linear light explicitly
+        match quantizer.encoding {
+            // rendering intent 0, perceptual
+            Encoding::Srgb => write_chunk(&mut w, b"sRGB", &[0])?,
+            Encoding::Gamma(g) => write_chunk(&mut w, b"gAMA", &((100_000.0 / g).round() as u32).to_be_bytes())?,
+            Encoding::Linear => {}
+        }
+
+        let bytes = depth as usize / 8;
+        let mut idat = IdatWriter {
+            w,
+            pending: Vec::with_capacity(MAX_BLOCK),
+            remaining: self.height * (1 + 3 * bytes * self.width),
+            adler: Adler32::adler32(),
+            started: false,
+        };
+        let mut row = Vec::with_capacity(1 + 3 * bytes * self.width);
+        for y in 0..self.height {
+            row.clear();
+            // each scanline starts with its filter type, 0 being "none"
+            row.push(0);
+            for x in 0..self.width {
+                for v in self.pixel_at(x, y).quantize(quantizer, x, y) {
+                    if bytes == 2 {
+                        row.extend_from_slice(&v.to_be_bytes());
+                    } else {
+                        row.push(v as u8);
+                    }
+                }
+            }
+            idat.push(&row)?;
+        }
+        let mut w = idat.finish()?;
+
+        write_chunk(&mut w, b"IEND", &[])?;
+        w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Color;
+
+    // splits a PNG file into (type, data) chunks, checking every CRC
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut result = Vec::new();
+        let mut pos = 8;
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc32(body), crc);
+            result.push((String::from_utf8(body[..4].to_vec()).unwrap(), body[4..].to_vec()));
+            pos += 12 + len;
+        }
+        result
+    }
+
+    // undoes the stored deflate blocks of a zlib stream, checking the Adler-32
+    fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        let mut out = Vec::new();
+        let mut pos = 2;
+        loop {
+            let last = zlib[pos] & 1 == 1;
+            assert_eq!(zlib[pos] >> 1, 0);
+            let len = u16::from_le_bytes([zlib[pos + 1], zlib[pos + 2]]);
+            let nlen = u16::from_le_bytes([zlib[pos + 3], zlib[pos + 4]]);
+            assert_eq!(len, !nlen);
+            out.extend_from_slice(&zlib[pos + 5..pos + 5 + len as usize]);
+            pos += 5 + len as usize;
+            if last {
+                break;
+            }
+        }
+        let mut adler = Adler32::adler32();
+        adler.update(&out);
+        assert_eq!(zlib[pos..], adler.value().to_be_bytes());
+        out
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        // every PNG ends with the same IEND chunk
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn test_adler32() {
+        let mut a = Adler32::adler32();
+        assert_eq!(a.value(), 1);
+        a.update(b"Wiki");
+        a.update(b"pedia");
+        assert_eq!(a.value(), 0x11e6_0398);
+
+        // large inputs wrap around the modulus
+        let mut a = Adler32::adler32();
+        a.update(&[255; 100_000]);
+        assert_eq!(a.value(), 0x149a_302c);
+    }
+
+    #[test]
+    fn test_write_png() {
+        let mut c = Canvas::canvas(3, 2);
+        c.write_pixel(0, 0, Color::color(1.0, 0.0, 0.0));
+        c.write_pixel(2, 1, Color::color(0.2, 0.4, 1.5));
+        let mut png = Vec::new();
+        c.write_png(&mut png, &Quantizer::quantizer()).unwrap();
+
+        let chunks = chunks(&png);
+        let kinds: Vec<&str> = chunks.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(
+            inflate_stored(&chunks[1].1),
+            [0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 51, 102, 255]
+        );
+        assert!(chunks[2].1.is_empty());
+    }
+
+    #[test]
+    fn test_write_png_gamma() {
+        let c = Canvas::canvas(1, 1);
+        for (gamma, stored) in [(1.0, 100_000u32), (2.2, 45_455)] {
+            let q = Quantizer {
+                encoding: Encoding::Gamma(gamma),
+                ..Quantizer::quantizer()
+            };
+            let mut png = Vec::new();
+            c.write_png(&mut png, &q).unwrap();
+            assert_eq!(chunks(&png)[1], ("gAMA".to_string(), stored.to_be_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_write_png_many_blocks() {
+        // 200 rows of 1 + 3 * 200 bytes need several stored blocks
+        let mut c = Canvas::canvas(200, 200);
+        for y in 0..200 {
+            for x in 0..200 {
+                c.write_pixel(x, y, Color::color(x as f64 / 256.0, y as f64 / 256.0, 0.5));
+            }
+        }
+        let mut png = Vec::new();
+        c.write_png(&mut png, &Quantizer::quantizer()).unwrap();
+
+        let chunks = chunks(&png);
+        let zlib: Vec<u8> = chunks.iter().filter(|(k, _)| k == "IDAT").flat_map(|(_, d)| d.clone()).collect();
+        assert_eq!(chunks.iter().filter(|(k, _)| k == "IDAT").count(), 2);
+        let raw = inflate_stored(&zlib);
+        assert_eq!(raw.len(), 200 * 601);
+        let (x, y) = (17, 123);
+        let p = y * 601 + 1 + 3 * x;
+        assert_eq!(raw[y * 601], 0);
+        assert_eq!(raw[p..p + 3], [17, 123, 128]);
+    }
+
+    #[test]
+    fn test_write_png_empty() {
+        for (w, h) in [(0, 0), (3, 0), (0, 3)] {
+            let mut png = Vec::new();
+            let err = Canvas::canvas(w, h).write_png(&mut png, &Quantizer::quantizer()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(png.is_empty());
+        }
+        // no pixels are allocated for a canvas with a zero side
+        let err = Canvas::canvas(1 << 31, 0).write_png(Vec::new(), &Quantizer::quantizer()).unwrap_err();
+        assert_eq!(err.to_string(), "canvas too large for PNG");
+    }
+
+    #[test]
+    fn test_write_png_16_bit_srgb() {
+        let mut c = Canvas::canvas(1, 1);
+        c.write_pixel(0, 0, Color::color(1.0, 0.5, 0.0));
+        let q = Quantizer {
+            max_value: 65535,
+            encoding: Encoding::Srgb,
+            dither: false,
+        };
+        let mut png = Vec::new();
+        c.write_png(&mut png, &q).unwrap();
+
+        let chunks = chunks(&png);
+        assert_eq!(chunks[0].1[8], 16);
+        assert_eq!(chunks[1], ("sRGB".to_string(), vec![0]));
+        let green = q.quantize(0.5, 0, 0).to_be_bytes();
+        assert_eq!(inflate_stored(&chunks[2].1), [0, 255, 255, green[0], green[1], 0, 0]);
+
+        let q = Quantizer {
+            max_value: 1000,
+            ..Quantizer::quantizer()
+        };
+        assert!(c.write_png(Vec::new(), &q).is_err());
+    }
+}
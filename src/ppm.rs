@@ -0,0 +1,386 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::canvas::Canvas;
+use crate::colors::{Color, Quantizer};
+
+/// The two PPM flavours: plain text (P3) and binary (P6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PpmFormat {
+    P3,
+    P6,
+}
+
+#[derive(Debug)]
+pub enum PpmError {
+    /// The file does not start with `P3` or `P6`.
+    BadMagic(String),
+    /// A header field or pixel value that is not a valid number, or is out of range.
+    InvalidValue(String),
+    /// The file ended before all the pixels were read.
+    Truncated,
+    Io(io::Error),
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::BadMagic(m) => write!(f, "not a P3 or P6 PPM file (magic number {:?})", m),
+            PpmError::InvalidValue(v) => write!(f, "invalid value {:?}", v),
+            PpmError::Truncated => write!(f, "unexpected end of file"),
+            PpmError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for PpmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PpmError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PpmError {
+    fn from(e: io::Error) -> PpmError {
+        PpmError::Io(e)
+    }
+}
+
+// Walks the header and, for P3, the pixel values: whitespace separated
+// tokens, with `#` comments running to the end of the line.
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next_token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.data.get(self.pos)? {
+                b'#' => {
+                    while self.data.get(self.pos).is_some_and(|b| *b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#') {
+            self.pos += 1;
+        }
+        Some(&self.data[start..self.pos])
+    }
+
+    fn number(&mut self, max: usize) -> Result<usize, PpmError> {
+        let token = self.next_token().ok_or(PpmError::Truncated)?;
+        let text = String::from_utf8_lossy(token);
+        match text.parse::<usize>() {
+            Ok(n) if n <= max => Ok(n),
+            _ => Err(PpmError::InvalidValue(text.into_owned())),
+        }
+    }
+}
+
+// P3 lines should not be longer than 70 characters
+const MAX_LINE: usize = 70;
+
+impl Canvas {
+    /// Writes the canvas as a PPM image one row at a time, so the whole file
+    /// never has to be held in memory. Wrap `w` in a `BufWriter` when writing
+    /// to a file. The quantizer's maximum value becomes the file's; P6
+    /// samples take two bytes when it is above 255.
+    pub fn write_ppm<W: Write>(&self, mut w: W, format: PpmFormat, quantizer: &Quantizer) -> io::Result<()> {
+        if quantizer.max_value == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "PPM maximum value must be positive"));
+        }
+        let magic = match format {
+            PpmFormat::P3 => "P3",
+            PpmFormat::P6 => "P6",
+        };
+        write!(w, "{}\n{} {}\n{}\n", magic, self.width, self.height, quantizer.max_value)?;
+
+        let wide = quantizer.max_value > 255;
+        let mut row = Vec::with_capacity(self.width * 12);
+        for y in 0..self.height {
+            row.clear();
+            match format {
+                PpmFormat::P3 => {
+                    let mut line_len = 0;
+                    for x in 0..self.width {
+                        for v in self.pixel_at(x, y).quantize(quantizer, x, y) {
+                            let text = v.to_string();
+                            if line_len > 0 && line_len + 1 + text.len() > MAX_LINE {
+                                row.push(b'\n');
+                                line_len = 0;
+                            } else if line_len > 0 {
+                                row.push(b' ');
+                                line_len += 1;
+                            }
+                            row.extend_from_slice(text.as_bytes());
+                            line_len += text.len();
+                        }
+                    }
+                    row.push(b'\n');
+                }
+                PpmFormat::P6 => {
+                    for x in 0..self.width {
+                        for v in self.pixel_at(x, y).quantize(quantizer, x, y) {
+                            if wide {
+                                row.extend_from_slice(&v.to_be_bytes());
+                            } else {
+                                row.push(v as u8);
+                            }
+                        }
+                    }
+                }
+            }
+            w.write_all(&row)?;
+        }
+        w.flush()
+    }
+
+    /// Parses a PPM image, plain (P3) or binary (P6). Color values are
+    /// rescaled from the file's maximum value to 0..1.
+    pub fn from_ppm(ppm: &str) -> Result<Canvas, PpmError> {
+        Canvas::from_ppm_bytes(ppm.as_bytes())
+    }
+
+    pub fn read_ppm<R: Read>(mut reader: R) -> Result<Canvas, PpmError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Canvas::from_ppm_bytes(&data)
+    }
+
+    pub fn from_ppm_bytes(data: &[u8]) -> Result<Canvas, PpmError> {
+        let mut tokens = Tokens { data, pos: 0 };
+        let magic = tokens.next_token().unwrap_or_default();
+        let binary = match magic {
+            b"P3" => false,
+            b"P6" => true,
+            _ => return Err(PpmError::BadMagic(String::from_utf8_lossy(magic).into_owned())),
+        };
+
+        let width = tokens.number(usize::MAX)?;
+        let height = tokens.number(usize::MAX)?;
+        let max = tokens.number(65535)?;
+        if max == 0 {
+            return Err(PpmError::InvalidValue("0".to_string()));
+        }
+
+        let mut samples = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(3))
+            .ok_or_else(|| PpmError::InvalidValue(format!("{}x{}", width, height)))?;
+        // nothing is allocated up front, so a bogus header can't ask for gigabytes
+        let mut values = Vec::new();
+        if binary {
+            // a single whitespace byte separates the header from the raster,
+            // and samples take two bytes (big-endian) when max is above 255
+            let start = tokens.pos + 1;
+            let size = if max < 256 { 1 } else { 2 };
+            let raster = data.get(start..).unwrap_or_default();
+            if raster.len() / size < samples {
+                return Err(PpmError::Truncated);
+            }
+            for chunk in raster.chunks_exact(size).take(samples) {
+                let v = chunk.iter().fold(0, |acc, b| (acc << 8) | *b as usize);
+                if v > max {
+                    return Err(PpmError::InvalidValue(v.to_string()));
+                }
+                values.push(v);
+            }
+        } else {
+            while samples > 0 {
+                values.push(tokens.number(max)?);
+                samples -= 1;
+            }
+        }
+
+        let mut canvas = Canvas::canvas(width, height);
+        let max = max as f64;
+        for (i, rgb) in values.chunks_exact(3).enumerate() {
+            let color = Color::color(rgb[0] as f64 / max, rgb[1] as f64 / max, rgb[2] as f64 / max);
+            canvas.write_pixel(i % width, i / width, color);
+        }
+        Ok(canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_canvas() -> Canvas {
+        let mut c = Canvas::canvas(10, 2);
+        for y in 0..2 {
+            for x in 0..10 {
+                c.write_pixel(x, y, Color::color(1.0, 0.8, 0.6));
+            }
+        }
+        c.write_pixel(3, 1, Color::color(-0.5, 0.5, 1.5));
+        c
+    }
+
+    #[test]
+    fn test_write_p3() {
+        let mut c = test_canvas();
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::P3, &Quantizer::quantizer()).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.lines().all(|l| l.len() <= MAX_LINE));
+        assert!(text.ends_with('\n'));
+        assert_eq!(Canvas::from_ppm(&text).unwrap().pixel_at(3, 1), Color::color(0.0, 128.0 / 255.0, 1.0));
+
+        // same output as `to_ppm` for the book's example
+        c.write_pixel(3, 1, Color::color(1.0, 0.8, 0.6));
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::P3, &Quantizer::quantizer()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), c.to_ppm());
+    }
+
+    #[test]
+    fn test_write_p6() {
+        let c = test_canvas();
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::P6, &Quantizer::quantizer()).unwrap();
+        assert!(out.starts_with(b"P6\n10 2\n255\n"));
+        assert_eq!(out.len(), 12 + 10 * 2 * 3);
+        assert_eq!(&out[12..15], &[255, 204, 153]);
+        assert_eq!(&out[12 + 3 * 13..12 + 3 * 14], &[0, 128, 255]);
+
+        let read = Canvas::from_ppm_bytes(&out).unwrap();
+        assert_eq!(read.pixel_at(3, 1), Color::color(0.0, 128.0 / 255.0, 1.0));
+        assert_eq!(read.pixel_at(9, 0), Color::color(1.0, 0.8, 0.6));
+    }
+
+    #[test]
+    fn test_write_quantized() {
+        let mut c = Canvas::canvas(2, 1);
+        c.write_pixel(0, 0, Color::color(0.5, 1.0, 0.0));
+        c.write_pixel(1, 0, Color::color(0.25, 0.75, 2.0));
+        let q = Quantizer {
+            max_value: 1000,
+            ..Quantizer::quantizer()
+        };
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::P3, &q).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "P3\n2 1\n1000\n500 1000 0 250 750 1000\n");
+
+        // 16 bit P6 round trips through the reader
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::P6, &q).unwrap();
+        assert_eq!(out.len(), 12 + 2 * 6);
+        let read = Canvas::from_ppm_bytes(&out).unwrap();
+        assert_eq!(read.pixel_at(1, 0), Color::color(0.25, 0.75, 1.0));
+
+        let q = Quantizer {
+            max_value: 0,
+            ..Quantizer::quantizer()
+        };
+        assert!(c.write_ppm(Vec::new(), PpmFormat::P3, &q).is_err());
+    }
+
+    #[test]
+    fn test_bad_magic() {
+        let result = Canvas::from_ppm("P32\n1 1\n255\n0 0 0\n");
+        assert!(matches!(result, Err(PpmError::BadMagic(m)) if m == "P32"));
+        assert!(matches!(Canvas::from_ppm(""), Err(PpmError::BadMagic(_))));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert!(matches!(Canvas::from_ppm("P3\n10 2\n255\n"), Err(PpmError::Truncated)));
+
+        let mut ppm = String::from("P3\n10 2\n255\n");
+        ppm.push_str(&"0 0 0\n".repeat(20));
+        let c = Canvas::from_ppm(&ppm).unwrap();
+        assert_eq!(c.width, 10);
+        assert_eq!(c.height, 2);
+    }
+
+    #[test]
+    fn test_pixels() {
+        let ppm = "P3
+4 3
+255
+255 127 0  0 127 255  127 255 0  255 255 255
+0 0 0  255 0 0  0 255 0  0 0 255
+255 255 0  0 255 255  255 0 255  127 127 127
+";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        let cases = [
+            (0, 0, (1.0, 0.49804, 0.0)),
+            (1, 0, (0.0, 0.49804, 1.0)),
+            (2, 0, (0.49804, 1.0, 0.0)),
+            (3, 0, (1.0, 1.0, 1.0)),
+            (0, 1, (0.0, 0.0, 0.0)),
+            (1, 1, (1.0, 0.0, 0.0)),
+            (2, 1, (0.0, 1.0, 0.0)),
+            (3, 1, (0.0, 0.0, 1.0)),
+            (0, 2, (1.0, 1.0, 0.0)),
+            (1, 2, (0.0, 1.0, 1.0)),
+            (2, 2, (1.0, 0.0, 1.0)),
+            (3, 2, (0.49804, 0.49804, 0.49804)),
+        ];
+        for (x, y, (r, g, b)) in cases {
+            let p = c.pixel_at(x, y);
+            assert!((p.red - r).abs() < 0.0001 && (p.green - g).abs() < 0.0001 && (p.blue - b).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_comments_and_whitespace() {
+        let ppm = "P3\n# this is a comment\n2 1\n# this, too\n255\n# another comment\n255 255 255\n# oh, no, comments in the pixel data!\n255 0 255\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::color(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(1, 0), Color::color(1.0, 0.0, 1.0));
+
+        // pixel data may be split over lines anywhere
+        let ppm = "P3\n1 1\n255\n51\n153\n\n204\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::color(0.2, 0.6, 0.8));
+    }
+
+    #[test]
+    fn test_max_value() {
+        let ppm = "P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        assert_eq!(c.pixel_at(0, 1), Color::color(0.75, 0.5, 0.25));
+
+        assert!(matches!(Canvas::from_ppm("P3\n1 1\n100\n101 0 0\n"), Err(PpmError::InvalidValue(_))));
+        assert!(matches!(Canvas::from_ppm("P3\n1 1\n0\n0 0 0\n"), Err(PpmError::InvalidValue(_))));
+        assert!(matches!(Canvas::from_ppm("P3\n1 x\n255\n0 0 0\n"), Err(PpmError::InvalidValue(_))));
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert!(matches!(Canvas::from_ppm("P3\n2 1\n255\n0 0 0\n"), Err(PpmError::Truncated)));
+        assert!(matches!(Canvas::from_ppm("P3\n2"), Err(PpmError::Truncated)));
+        assert!(matches!(Canvas::from_ppm("P6\n400000000 400000000\n255\n"), Err(PpmError::Truncated)));
+        assert!(matches!(
+            Canvas::from_ppm("P3\n99999999999 99999999999\n255\n"),
+            Err(PpmError::InvalidValue(_))
+        ));
+        assert!(matches!(Canvas::from_ppm_bytes(b"P6\n2 1\n255\n\x00\x00\x00\xff"), Err(PpmError::Truncated)));
+    }
+
+    #[test]
+    fn test_binary() {
+        let mut data = b"P6\n# binary\n2 1\n255\n".to_vec();
+        data.extend_from_slice(&[255, 0, 51, 0, 255, 10]);
+        let c = Canvas::from_ppm_bytes(&data).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::color(1.0, 0.0, 0.2));
+        assert_eq!(c.pixel_at(1, 0), Color::color(0.0, 1.0, 10.0 / 255.0));
+
+        // 16 bit samples are big-endian
+        let mut data = b"P6 1 1 65535\n".to_vec();
+        data.extend_from_slice(&[0xff, 0xff, 0x80, 0x00, 0x00, 0x00]);
+        let c = Canvas::read_ppm(data.as_slice()).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::color(1.0, 32768.0 / 65535.0, 0.0));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::matrices::Matrix;
+use crate::tuples::Tuple;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Tuple,
+    pub direction: Tuple,
+}
+
+impl Ray {
+    pub fn ray(origin: Tuple, direction: Tuple) -> Ray {
+        Ray { origin, direction }
+    }
+
+    pub fn position(&self, t: f64) -> Tuple {
+        self.origin + self.direction * t
+    }
+
+    pub fn transform(&self, m: &Matrix) -> Ray {
+        Ray {
+            origin: m * self.origin,
+            direction: m * self.direction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformations::{scaling, translation};
+
+    #[test]
+    fn test_fn() {
+        let origin = Tuple::point(1.0, 2.0, 3.0);
+        let direction = Tuple::vector(4.0, 5.0, 6.0);
+        let r = Ray::ray(origin, direction);
+        assert_eq!(r.origin, origin);
+        assert_eq!(r.direction, direction);
+    }
+
+    #[test]
+    fn test_position() {
+        let r = Ray::ray(Tuple::point(2.0, 3.0, 4.0), Tuple::vector(1.0, 0.0, 0.0));
+        assert_eq!(r.position(0.0), Tuple::point(2.0, 3.0, 4.0));
+        assert_eq!(r.position(1.0), Tuple::point(3.0, 3.0, 4.0));
+        assert_eq!(r.position(-1.0), Tuple::point(1.0, 3.0, 4.0));
+        assert_eq!(r.position(2.5), Tuple::point(4.5, 3.0, 4.0));
+    }
+
+    #[test]
+    fn test_transform() {
+        let r = Ray::ray(Tuple::point(1.0, 2.0, 3.0), Tuple::vector(0.0, 1.0, 0.0));
+
+        let r2 = r.transform(&translation(3.0, 4.0, 5.0));
+        assert_eq!(r2.origin, Tuple::point(4.0, 6.0, 8.0));
+        assert_eq!(r2.direction, Tuple::vector(0.0, 1.0, 0.0));
+
+        let r3 = r.transform(&scaling(2.0, 3.0, 4.0));
+        assert_eq!(r3.origin, Tuple::point(2.0, 6.0, 12.0));
+        assert_eq!(r3.direction, Tuple::vector(0.0, 3.0, 0.0));
+        // the original ray is left untouched
+        assert_eq!(r.origin, Tuple::point(1.0, 2.0, 3.0));
+    }
+}
@@ -0,0 +1,1208 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use yaml_rust::parser::{Event, Parser};
+
+use crate::camera::Camera;
+use crate::canvas::Canvas;
+use crate::colors::Color;
+use crate::cones::Cone;
+use crate::csg::{Csg, CsgOperation};
+use crate::cubes::Cube;
+use crate::cylinders::Cylinder;
+use crate::groups::Group;
+use crate::lights::{AreaLight, DirectionalLight, Light, PointLight, SpotLight};
+use crate::materials::Material;
+use crate::matrices::Matrix;
+use crate::obj::parse_obj_file;
+use crate::patterns::{
+    CheckersPattern, GradientPattern, Pattern, RadialGradientPattern, RingPattern, StripePattern,
+};
+use crate::planes::Plane;
+use crate::shapes::Shape;
+use crate::spheres::Sphere;
+use crate::textures::{CubeMapPattern, TextureMapPattern, UvAlignCheck, UvCheckers, UvImage, UvMapping, UvPattern};
+use crate::transformations::{rotation_x, rotation_y, rotation_z, scaling, shearing, translation, view_transform};
+use crate::tuples::Tuple;
+use crate::world::World;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneErrorKind {
+    /// The file is not valid YAML.
+    Syntax(String),
+    /// A value of the wrong type; says what was expected instead.
+    Expected(String),
+    MissingKey(String),
+    UnknownKey(String),
+    /// An `add`, `extend`, material or transform name that is neither built
+    /// in nor defined earlier in the file.
+    UnknownName(String),
+    /// The scene has no camera to render it with.
+    MissingCamera,
+    /// A file the scene refers to could not be read or parsed.
+    File(String),
+}
+
+/// A problem with a scene file. `line` is 1-based and `path` leads to the
+/// offending value, e.g. `[3].material.color`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneError {
+    pub line: usize,
+    pub path: String,
+    pub kind: SceneErrorKind,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        match &self.kind {
+            SceneErrorKind::Syntax(m) => write!(f, "{}", m),
+            SceneErrorKind::Expected(what) => write!(f, "expected {}", what),
+            SceneErrorKind::MissingKey(k) => write!(f, "missing key {:?}", k),
+            SceneErrorKind::UnknownKey(k) => write!(f, "unknown key {:?}", k),
+            SceneErrorKind::UnknownName(n) => write!(f, "unknown name {:?}", n),
+            SceneErrorKind::MissingCamera => write!(f, "no camera in scene"),
+            SceneErrorKind::File(m) => write!(f, "{}", m),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+/// Everything needed to render a scene file.
+#[derive(Debug)]
+pub struct Scene {
+    pub world: World,
+    pub camera: Camera,
+}
+
+/// Parses a scene in the YAML format of the Ray Tracer Challenge bonus
+/// scenes: a list of `add:` entries for the camera, lights and shapes, and
+/// `define:` entries naming materials, transforms and shapes for later use.
+/// Files the scene refers to are looked up relative to the current directory.
+pub fn parse_scene(input: &str) -> Result<Scene, SceneError> {
+    Loader::loader(PathBuf::new()).load(input)
+}
+
+/// Reads and parses a scene file. Files it refers to are looked up relative
+/// to the scene file's directory.
+pub fn load_scene(path: &Path) -> Result<Scene, SceneError> {
+    let input = fs::read_to_string(path).map_err(|e| SceneError {
+        line: 0,
+        path: String::new(),
+        kind: SceneErrorKind::File(format!("{}: {}", path.display(), e)),
+    })?;
+    let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    Loader::loader(base).load(&input)
+}
+
+// The parsed YAML, keeping the line each value started on so errors can
+// point at it.
+#[derive(Debug, Clone)]
+enum Value {
+    Scalar(String),
+    List(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    value: Value,
+    line: usize,
+}
+
+fn key_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn index_path(path: &str, i: usize) -> String {
+    format!("{}[{}]", path, i)
+}
+
+impl Node {
+    fn error(&self, path: &str, kind: SceneErrorKind) -> SceneError {
+        SceneError {
+            line: self.line,
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    fn expected(&self, path: &str, what: &str) -> SceneError {
+        self.error(path, SceneErrorKind::Expected(what.to_string()))
+    }
+
+    fn as_map(&self, path: &str) -> Result<&[(String, Node)], SceneError> {
+        match &self.value {
+            Value::Map(entries) => Ok(entries),
+            _ => Err(self.expected(path, "a mapping")),
+        }
+    }
+
+    fn as_list(&self, path: &str) -> Result<&[Node], SceneError> {
+        match &self.value {
+            Value::List(items) => Ok(items),
+            _ => Err(self.expected(path, "a list")),
+        }
+    }
+
+    fn as_str(&self, path: &str) -> Result<&str, SceneError> {
+        match &self.value {
+            Value::Scalar(s) => Ok(s),
+            _ => Err(self.expected(path, "a name")),
+        }
+    }
+
+    fn as_f64(&self, path: &str) -> Result<f64, SceneError> {
+        match &self.value {
+            Value::Scalar(s) => s.parse().map_err(|_| self.expected(path, "a number")),
+            _ => Err(self.expected(path, "a number")),
+        }
+    }
+
+    fn as_usize(&self, path: &str) -> Result<usize, SceneError> {
+        match &self.value {
+            Value::Scalar(s) => match s.parse() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(self.expected(path, "a positive whole number")),
+            },
+            _ => Err(self.expected(path, "a positive whole number")),
+        }
+    }
+
+    fn as_bool(&self, path: &str) -> Result<bool, SceneError> {
+        match &self.value {
+            Value::Scalar(s) if s == "true" => Ok(true),
+            Value::Scalar(s) if s == "false" => Ok(false),
+            _ => Err(self.expected(path, "true or false")),
+        }
+    }
+
+    fn as_numbers(&self, count: usize, path: &str) -> Result<Vec<f64>, SceneError> {
+        let what = format!("a list of {} numbers", count);
+        match &self.value {
+            Value::List(items) if items.len() == count => {
+                items.iter().enumerate().map(|(i, n)| n.as_f64(&index_path(path, i))).collect()
+            }
+            _ => Err(self.expected(path, &what)),
+        }
+    }
+
+    fn as_point(&self, path: &str) -> Result<Tuple, SceneError> {
+        let n = self.as_numbers(3, path)?;
+        Ok(Tuple::point(n[0], n[1], n[2]))
+    }
+
+    fn as_vector(&self, path: &str) -> Result<Tuple, SceneError> {
+        let n = self.as_numbers(3, path)?;
+        Ok(Tuple::vector(n[0], n[1], n[2]))
+    }
+
+    fn as_color(&self, path: &str) -> Result<Color, SceneError> {
+        let n = self.as_numbers(3, path)?;
+        Ok(Color::color(n[0], n[1], n[2]))
+    }
+
+    fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn require(&self, key: &str, path: &str) -> Result<&Node, SceneError> {
+        self.get(key).ok_or_else(|| self.error(path, SceneErrorKind::MissingKey(key.to_string())))
+    }
+}
+
+// a container still being filled in, with its line and anchor
+enum Frame {
+    List(Vec<Node>, usize, usize),
+    Map(Vec<(String, Node)>, Option<String>, usize, usize),
+}
+
+fn parse_yaml(input: &str) -> Result<Option<Node>, SceneError> {
+    let mut parser = Parser::new(input.chars());
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut anchors: HashMap<usize, Node> = HashMap::new();
+
+    loop {
+        let (event, mark) = parser.next().map_err(|e| {
+            // the message repeats the position, which the error carries anyway
+            let message = e.to_string();
+            let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(m, _)| m);
+            SceneError {
+                line: e.marker().line(),
+                path: String::new(),
+                kind: SceneErrorKind::Syntax(message.to_string()),
+            }
+        })?;
+        let line = mark.line();
+
+        let (node, anchor) = match event {
+            Event::StreamEnd | Event::DocumentEnd => return Ok(None),
+            Event::Scalar(value, _, anchor, _) => (Node { value: Value::Scalar(value), line }, anchor),
+            Event::Alias(id) => match anchors.get(&id) {
+                Some(node) => (node.clone(), 0),
+                None => {
+                    return Err(SceneError {
+                        line,
+                        path: String::new(),
+                        kind: SceneErrorKind::Syntax("unknown alias".to_string()),
+                    })
+                }
+            },
+            Event::SequenceStart(anchor) => {
+                stack.push(Frame::List(Vec::new(), line, anchor));
+                continue;
+            }
+            Event::MappingStart(anchor) => {
+                stack.push(Frame::Map(Vec::new(), None, line, anchor));
+                continue;
+            }
+            Event::SequenceEnd | Event::MappingEnd => match stack.pop() {
+                Some(Frame::List(items, line, anchor)) => (Node { value: Value::List(items), line }, anchor),
+                Some(Frame::Map(entries, _, line, anchor)) => (Node { value: Value::Map(entries), line }, anchor),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        if anchor > 0 {
+            anchors.insert(anchor, node.clone());
+        }
+        match stack.last_mut() {
+            None => return Ok(Some(node)),
+            Some(Frame::List(items, _, _)) => items.push(node),
+            Some(Frame::Map(entries, key, _, _)) => match key.take() {
+                Some(k) => entries.push((k, node)),
+                None => match node.value {
+                    Value::Scalar(k) => *key = Some(k),
+                    _ => return Err(node.expected("", "a plain key")),
+                },
+            },
+        }
+    }
+}
+
+// how deeply defined names may refer to each other, which stops cycles
+const MAX_NESTING: usize = 32;
+
+// `value` laid over `base`, key by key, as `extend` does
+fn merge(base: &Node, value: &Node) -> Node {
+    match (&base.value, &value.value) {
+        (Value::Map(base_entries), Value::Map(entries)) => {
+            let mut merged: Vec<(String, Node)> =
+                base_entries.iter().filter(|(k, _)| value.get(k).is_none()).cloned().collect();
+            merged.extend(entries.iter().cloned());
+            Node {
+                value: Value::Map(merged),
+                line: value.line,
+            }
+        }
+        _ => value.clone(),
+    }
+}
+
+struct Loader {
+    defines: HashMap<String, Node>,
+    base: PathBuf,
+    world: World,
+    camera: Option<Camera>,
+}
+
+impl Loader {
+    fn loader(base: PathBuf) -> Loader {
+        Loader {
+            defines: HashMap::new(),
+            base,
+            world: World::world(),
+            camera: None,
+        }
+    }
+
+    fn load(mut self, input: &str) -> Result<Scene, SceneError> {
+        let root = match parse_yaml(input)? {
+            Some(root) => root,
+            None => Node {
+                value: Value::List(Vec::new()),
+                line: 1,
+            },
+        };
+        for (i, item) in root.as_list("")?.iter().enumerate() {
+            let path = index_path("", i);
+            item.as_map(&path)?;
+            if let Some(name) = item.get("define") {
+                self.define(item, name.as_str(&key_path(&path, "define"))?, &path)?;
+            } else if let Some(kind) = item.get("add") {
+                match kind.as_str(&key_path(&path, "add"))? {
+                    "camera" => self.camera = Some(self.camera(item, &path)?),
+                    "light" => {
+                        let light = self.light(item, &path)?;
+                        self.world.lights.push(light);
+                    }
+                    _ => {
+                        let shape = self.shape(item, &path, 0)?;
+                        self.world.objects.push(shape);
+                    }
+                }
+            } else {
+                return Err(item.error(&path, SceneErrorKind::MissingKey("add".to_string())));
+            }
+        }
+
+        let line = input.lines().count().max(1);
+        match self.camera {
+            Some(camera) => Ok(Scene {
+                world: self.world,
+                camera,
+            }),
+            None => Err(SceneError {
+                line,
+                path: String::new(),
+                kind: SceneErrorKind::MissingCamera,
+            }),
+        }
+    }
+
+    fn file(&self, node: &Node, path: &str) -> Result<(PathBuf, Vec<u8>), SceneError> {
+        let file = self.base.join(node.as_str(path)?);
+        let data = fs::read(&file)
+            .map_err(|e| node.error(path, SceneErrorKind::File(format!("{}: {}", file.display(), e))))?;
+        Ok((file, data))
+    }
+
+    fn lookup(&self, node: &Node, path: &str) -> Result<&Node, SceneError> {
+        let name = node.as_str(path)?;
+        self.defines
+            .get(name)
+            .ok_or_else(|| node.error(path, SceneErrorKind::UnknownName(name.to_string())))
+    }
+
+    fn define(&mut self, item: &Node, name: &str, path: &str) -> Result<(), SceneError> {
+        let mut value = item.require("value", path)?.clone();
+        for (key, v) in item.as_map(path)? {
+            let p = key_path(path, key);
+            match key.as_str() {
+                "define" | "value" => {}
+                "extend" => value = merge(self.lookup(v, &p)?, &value),
+                _ => return Err(v.error(&p, SceneErrorKind::UnknownKey(key.clone()))),
+            }
+        }
+        self.defines.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn camera(&self, item: &Node, path: &str) -> Result<Camera, SceneError> {
+        for (key, v) in item.as_map(path)? {
+            if !["add", "width", "height", "field-of-view", "from", "to", "up"].contains(&key.as_str()) {
+                return Err(v.error(&key_path(path, key), SceneErrorKind::UnknownKey(key.clone())));
+            }
+        }
+        let value = |key: &str| item.require(key, path).map(|v| (v, key_path(path, key)));
+
+        let (v, p) = value("width")?;
+        let width = v.as_usize(&p)?;
+        let (v, p) = value("height")?;
+        let height = v.as_usize(&p)?;
+        let (v, p) = value("field-of-view")?;
+        let mut camera = Camera::camera(width, height, v.as_f64(&p)?);
+
+        let (v, p) = value("from")?;
+        let from = v.as_point(&p)?;
+        let (v, p) = value("to")?;
+        let to = v.as_point(&p)?;
+        let (v, p) = value("up")?;
+        let up = v.as_vector(&p)?;
+        let transform = view_transform(from, to, up);
+        if transform.inverse().is_none() {
+            return Err(item.expected(path, "a camera that is not looking along `up`"));
+        }
+        camera.set_transform(transform);
+        Ok(camera)
+    }
+
+    // Point lights have `at`, area lights `corner`, directional lights only a
+    // `direction` and spot lights both `at` and `direction`.
+    fn light(&self, item: &Node, path: &str) -> Result<Light, SceneError> {
+        let mut fields: HashMap<&str, (&Node, String)> = HashMap::new();
+        for (key, v) in item.as_map(path)? {
+            let p = key_path(path, key);
+            match key.as_str() {
+                "add" => {}
+                "at" | "intensity" | "corner" | "uvec" | "vvec" | "usteps" | "vsteps" | "jitter" | "direction"
+                | "inner-angle" | "outer-angle" => {
+                    fields.insert(key, (v, p));
+                }
+                _ => return Err(v.error(&p, SceneErrorKind::UnknownKey(key.clone()))),
+            }
+        }
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .ok_or_else(|| item.error(path, SceneErrorKind::MissingKey(key.to_string())))
+        };
+
+        let (v, p) = field("intensity")?;
+        let intensity = v.as_color(p)?;
+
+        if fields.contains_key("corner") {
+            let (v, p) = field("corner")?;
+            let corner = v.as_point(p)?;
+            let (v, p) = field("uvec")?;
+            let uvec = v.as_vector(p)?;
+            let (v, p) = field("usteps")?;
+            let usteps = v.as_usize(p)?;
+            let (v, p) = field("vvec")?;
+            let vvec = v.as_vector(p)?;
+            let (v, p) = field("vsteps")?;
+            let vsteps = v.as_usize(p)?;
+            let mut light = AreaLight::area_light(corner, uvec, usteps, vvec, vsteps, intensity);
+            if let Some((v, p)) = fields.get("jitter") {
+                if v.as_bool(p)? {
+                    light.jitter = true;
+                }
+            }
+            return Ok(light.into());
+        }
+
+        match (fields.contains_key("at"), fields.contains_key("direction")) {
+            (true, false) => {
+                let (v, p) = field("at")?;
+                Ok(PointLight::point_light(v.as_point(p)?, intensity).into())
+            }
+            (false, true) => {
+                let (v, p) = field("direction")?;
+                Ok(DirectionalLight::directional_light(v.as_vector(p)?, intensity).into())
+            }
+            (true, true) => {
+                let (v, p) = field("at")?;
+                let position = v.as_point(p)?;
+                let (v, p) = field("direction")?;
+                let direction = v.as_vector(p)?;
+                let (v, p) = field("outer-angle")?;
+                let outer = v.as_f64(p)?;
+                let inner = match fields.get("inner-angle") {
+                    Some((v, p)) => v.as_f64(p)?,
+                    None => outer,
+                };
+                Ok(SpotLight::spot_light(position, direction, inner, outer, intensity).into())
+            }
+            (false, false) => Err(item.error(path, SceneErrorKind::MissingKey("at".to_string()))),
+        }
+    }
+
+    fn shape(&self, item: &Node, path: &str, depth: usize) -> Result<Box<dyn Shape>, SceneError> {
+        let add = item.require("add", path)?;
+        let add_path = key_path(path, "add");
+        let kind = add.as_str(&add_path)?;
+
+        let (mut shape, own_keys): (Box<dyn Shape>, &[&str]) = match kind {
+            "sphere" => (Box::new(Sphere::sphere()), &[]),
+            "plane" => (Box::new(Plane::plane()), &[]),
+            "cube" => (Box::new(Cube::cube()), &[]),
+            "cylinder" | "cone" => {
+                let mut minimum = f64::NEG_INFINITY;
+                let mut maximum = f64::INFINITY;
+                let mut closed = false;
+                if let Some(v) = item.get("min") {
+                    minimum = v.as_f64(&key_path(path, "min"))?;
+                }
+                if let Some(v) = item.get("max") {
+                    maximum = v.as_f64(&key_path(path, "max"))?;
+                }
+                if let Some(v) = item.get("closed") {
+                    closed = v.as_bool(&key_path(path, "closed"))?;
+                }
+                let shape: Box<dyn Shape> = if kind == "cylinder" {
+                    let mut c = Cylinder::cylinder();
+                    (c.minimum, c.maximum, c.closed) = (minimum, maximum, closed);
+                    Box::new(c)
+                } else {
+                    let mut c = Cone::cone();
+                    (c.minimum, c.maximum, c.closed) = (minimum, maximum, closed);
+                    Box::new(c)
+                };
+                (shape, &["min", "max", "closed"])
+            }
+            "group" => {
+                let mut group = Group::group();
+                if let Some(children) = item.get("children") {
+                    let p = key_path(path, "children");
+                    for (i, child) in children.as_list(&p)?.iter().enumerate() {
+                        group.add_child(self.shape(child, &index_path(&p, i), depth)?);
+                    }
+                }
+                (Box::new(group), &["children"])
+            }
+            "obj" => {
+                let p = key_path(path, "file");
+                let file = item.require("file", path)?;
+                let (name, data) = self.file(file, &p)?;
+                let text = String::from_utf8_lossy(&data);
+                let obj = parse_obj_file(&text)
+                    .map_err(|e| file.error(&p, SceneErrorKind::File(format!("{}: {}", name.display(), e))))?;
+                (Box::new(obj.to_group()), &["file"])
+            }
+            "csg" => {
+                let p = key_path(path, "operation");
+                let op = item.require("operation", path)?;
+                let operation = match op.as_str(&p)? {
+                    "union" => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    "difference" => CsgOperation::Difference,
+                    _ => return Err(op.expected(&p, "union, intersection or difference")),
+                };
+                let left = self.shape(item.require("left", path)?, &key_path(path, "left"), depth)?;
+                let right = self.shape(item.require("right", path)?, &key_path(path, "right"), depth)?;
+                (Box::new(Csg::csg(operation, left, right)), &["operation", "left", "right"])
+            }
+            _ => {
+                // a shape defined earlier; this entry's keys override its own
+                if depth >= MAX_NESTING {
+                    return Err(add.expected(&add_path, "definitions that do not refer to themselves"));
+                }
+                let defined = self.lookup(add, &add_path)?;
+                defined.require("add", &add_path)?;
+                let mut overrides = item.clone();
+                if let Value::Map(entries) = &mut overrides.value {
+                    entries.retain(|(k, _)| k != "add");
+                }
+                return self.shape(&merge(defined, &overrides), path, depth + 1);
+            }
+        };
+
+        for (key, v) in item.as_map(path)? {
+            let p = key_path(path, key);
+            match key.as_str() {
+                "add" => {}
+                "material" => *shape.material_mut() = self.material(v, &p)?,
+                "transform" => shape.set_transform(self.transform(v, &p, 0)?),
+                k if own_keys.contains(&k) => {}
+                _ => return Err(v.error(&p, SceneErrorKind::UnknownKey(key.clone()))),
+            }
+        }
+        Ok(shape)
+    }
+
+    fn material(&self, node: &Node, path: &str) -> Result<Material, SceneError> {
+        let node = match node.value {
+            Value::Scalar(_) => self.lookup(node, path)?,
+            _ => node,
+        };
+        let mut m = Material::material();
+        for (key, v) in node.as_map(path)? {
+            let p = key_path(path, key);
+            match key.as_str() {
+                "color" => m.color = v.as_color(&p)?,
+                "pattern" => m.pattern = Some(self.pattern(v, &p)?),
+                "ambient" => m.ambient = v.as_f64(&p)?,
+                "diffuse" => m.diffuse = v.as_f64(&p)?,
+                "specular" => m.specular = v.as_f64(&p)?,
+                "shininess" => m.shininess = v.as_f64(&p)?,
+                "reflective" => m.reflective = v.as_f64(&p)?,
+                "transparency" => m.transparency = v.as_f64(&p)?,
+                "refractive-index" => m.refractive_index = v.as_f64(&p)?,
+                _ => return Err(v.error(&p, SceneErrorKind::UnknownKey(key.clone()))),
+            }
+        }
+        Ok(m)
+    }
+
+    // Transforms are listed in the order they apply, each either an
+    // operation like `[ translate, 1, 2, 3 ]` or the name of a defined list.
+    fn transform(&self, node: &Node, path: &str, depth: usize) -> Result<Matrix, SceneError> {
+        let mut m = Matrix::identity();
+        for (i, step) in node.as_list(path)?.iter().enumerate() {
+            let p = index_path(path, i);
+            let t = match &step.value {
+                Value::Scalar(_) => {
+                    if depth >= MAX_NESTING {
+                        return Err(step.expected(&p, "definitions that do not refer to themselves"));
+                    }
+                    self.transform(self.lookup(step, &p)?, &p, depth + 1)?
+                }
+                Value::List(parts) if !parts.is_empty() => {
+                    let op = parts[0].as_str(&index_path(&p, 0))?;
+                    let args = |count: usize| -> Result<Vec<f64>, SceneError> {
+                        if parts.len() != count + 1 {
+                            let what = format!("{} followed by {} numbers", op, count);
+                            return Err(step.expected(&p, &what));
+                        }
+                        parts[1..].iter().enumerate().map(|(j, n)| n.as_f64(&index_path(&p, j + 1))).collect()
+                    };
+                    match op {
+                        "translate" => {
+                            let a = args(3)?;
+                            translation(a[0], a[1], a[2])
+                        }
+                        "scale" => {
+                            let a = args(3)?;
+                            scaling(a[0], a[1], a[2])
+                        }
+                        "rotate-x" => rotation_x(args(1)?[0]),
+                        "rotate-y" => rotation_y(args(1)?[0]),
+                        "rotate-z" => rotation_z(args(1)?[0]),
+                        "shear" => {
+                            let a = args(6)?;
+                            shearing(a[0], a[1], a[2], a[3], a[4], a[5])
+                        }
+                        _ => {
+                            return Err(parts[0].error(&index_path(&p, 0), SceneErrorKind::UnknownName(op.to_string())))
+                        }
+                    }
+                }
+                _ => return Err(step.expected(&p, "a transformation")),
+            };
+            m = &t * &m;
+        }
+        // shapes and patterns can only use transforms they can undo
+        if m.inverse().is_none() {
+            return Err(node.expected(path, "an invertible transformation"));
+        }
+        Ok(m)
+    }
+
+    fn colors(&self, node: &Node, path: &str) -> Result<(Color, Color), SceneError> {
+        let colors = node.require("colors", path)?;
+        let p = key_path(path, "colors");
+        match colors.as_list(&p)? {
+            [a, b] => Ok((a.as_color(&index_path(&p, 0))?, b.as_color(&index_path(&p, 1))?)),
+            _ => Err(colors.expected(&p, "a list of 2 colors")),
+        }
+    }
+
+    fn pattern(&self, node: &Node, path: &str) -> Result<Arc<dyn Pattern>, SceneError> {
+        let kind_node = node.require("type", path)?;
+        let type_path = key_path(path, "type");
+        let kind = kind_node.as_str(&type_path)?;
+        let (pattern, own_keys): (Box<dyn Pattern>, &[&str]) = match kind {
+            "stripes" | "gradient" | "rings" | "checkers" | "radial-gradient" => {
+                let (a, b) = self.colors(node, path)?;
+                let pattern: Box<dyn Pattern> = match kind {
+                    "stripes" => Box::new(StripePattern::stripe_pattern(a, b)),
+                    "gradient" => Box::new(GradientPattern::gradient_pattern(a, b)),
+                    "rings" => Box::new(RingPattern::ring_pattern(a, b)),
+                    "checkers" => Box::new(CheckersPattern::checkers_pattern(a, b)),
+                    _ => Box::new(RadialGradientPattern::radial_gradient_pattern(a, b)),
+                };
+                (pattern, &["colors"])
+            }
+            "map" => {
+                let p = key_path(path, "mapping");
+                let mapping_node = node.require("mapping", path)?;
+                let uv = |key: &str| self.uv_pattern(node.require(key, path)?, &key_path(path, key));
+                let mapping = match mapping_node.as_str(&p)? {
+                    "planar" => UvMapping::Planar,
+                    "spherical" => UvMapping::Spherical,
+                    "cylindrical" => UvMapping::Cylindrical,
+                    "cube" => {
+                        // a cube takes a pattern of its own for every face
+                        let pattern = CubeMapPattern::cube_map_pattern(
+                            uv("left")?,
+                            uv("front")?,
+                            uv("right")?,
+                            uv("back")?,
+                            uv("up")?,
+                            uv("down")?,
+                        );
+                        return self.finish_pattern(
+                            Box::new(pattern),
+                            node,
+                            path,
+                            &["mapping", "left", "front", "right", "back", "up", "down"],
+                        );
+                    }
+                    _ => return Err(mapping_node.expected(&p, "planar, spherical, cylindrical or cube")),
+                };
+                let pattern = TextureMapPattern::texture_map_pattern(uv("uv_pattern")?, mapping);
+                (Box::new(pattern), &["mapping", "uv_pattern"])
+            }
+            _ => return Err(kind_node.error(&type_path, SceneErrorKind::UnknownName(kind.to_string()))),
+        };
+        self.finish_pattern(pattern, node, path, own_keys)
+    }
+
+    // Applies the keys every pattern shares and rejects any it doesn't know.
+    fn finish_pattern(
+        &self,
+        mut pattern: Box<dyn Pattern>,
+        node: &Node,
+        path: &str,
+        own_keys: &[&str],
+    ) -> Result<Arc<dyn Pattern>, SceneError> {
+        for (key, v) in node.as_map(path)? {
+            let p = key_path(path, key);
+            match key.as_str() {
+                "type" => {}
+                "transform" => pattern.set_transform(self.transform(v, &p, 0)?),
+                k if own_keys.contains(&k) => {}
+                _ => return Err(v.error(&p, SceneErrorKind::UnknownKey(key.clone()))),
+            }
+        }
+        Ok(Arc::from(pattern))
+    }
+
+    fn uv_pattern(&self, node: &Node, path: &str) -> Result<Arc<dyn UvPattern>, SceneError> {
+        let kind_node = node.require("type", path)?;
+        let type_path = key_path(path, "type");
+        let (pattern, own_keys): (Arc<dyn UvPattern>, &[&str]) = match kind_node.as_str(&type_path)? {
+            "checkers" => {
+                let (a, b) = self.colors(node, path)?;
+                let width = node.require("width", path)?.as_f64(&key_path(path, "width"))?;
+                let height = node.require("height", path)?.as_f64(&key_path(path, "height"))?;
+                (Arc::new(UvCheckers::uv_checkers(width, height, a, b)), &["colors", "width", "height"])
+            }
+            "align_check" => {
+                let p = key_path(path, "colors");
+                let colors = node.require("colors", path)?;
+                let color = |key: &str| colors.require(key, &p)?.as_color(&key_path(&p, key));
+                let pattern =
+                    UvAlignCheck::uv_align_check(color("main")?, color("ul")?, color("ur")?, color("bl")?, color("br")?);
+                (Arc::new(pattern), &["colors"])
+            }
+            "image" => {
+                let p = key_path(path, "file");
+                let file = node.require("file", path)?;
+                let (name, data) = self.file(file, &p)?;
+                let canvas = Canvas::from_ppm_bytes(&data)
+                    .map_err(|e| file.error(&p, SceneErrorKind::File(format!("{}: {}", name.display(), e))))?;
+                if canvas.width == 0 || canvas.height == 0 {
+                    return Err(file.expected(&p, "an image with at least one pixel"));
+                }
+                (Arc::new(UvImage::uv_image(canvas)), &["file"])
+            }
+            kind => return Err(kind_node.error(&type_path, SceneErrorKind::UnknownName(kind.to_string()))),
+        };
+        for (key, v) in node.as_map(path)? {
+            if key != "type" && !own_keys.contains(&key.as_str()) {
+                return Err(v.error(&key_path(path, key), SceneErrorKind::UnknownKey(key.clone())));
+            }
+        }
+        Ok(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const CAMERA: &str = "
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [ -6, 6, -10 ]
+  to: [ 6, 0, 6 ]
+  up: [ -0.45, 1, 0 ]
+";
+
+    fn scene(items: &str) -> Result<Scene, SceneError> {
+        parse_scene(&format!("{}{}", CAMERA, items))
+    }
+
+    fn error(items: &str) -> (usize, String, SceneErrorKind) {
+        let e = scene(items).unwrap_err();
+        (e.line, e.path, e.kind)
+    }
+
+    #[test]
+    fn test_camera() {
+        let s = scene("").unwrap();
+        assert_eq!((s.camera.hsize(), s.camera.vsize()), (100, 50));
+        assert_eq!(s.camera.field_of_view(), 0.785);
+        let expected = view_transform(
+            Tuple::point(-6.0, 6.0, -10.0),
+            Tuple::point(6.0, 0.0, 6.0),
+            Tuple::vector(-0.45, 1.0, 0.0),
+        );
+        assert_eq!(*s.camera.transform(), expected);
+        assert!(s.world.objects.is_empty());
+        assert!(s.world.lights.is_empty());
+    }
+
+    #[test]
+    fn test_lights() {
+        let s = scene(
+            "
+- add: light
+  at: [ 50, 100, -50 ]
+  intensity: [ 1, 1, 1 ]
+- add: light
+  corner: [ -1, 2, 4 ]
+  uvec: [ 2, 0, 0 ]
+  vvec: [ 0, 2, 0 ]
+  usteps: 10
+  vsteps: 10
+  jitter: true
+  intensity: [ 1.5, 1.5, 1.5 ]
+- add: light
+  direction: [ 0, -2, 0 ]
+  intensity: [ 0.5, 0.5, 0.5 ]
+- add: light
+  at: [ 0, 5, 0 ]
+  direction: [ 0, -1, 0 ]
+  inner-angle: 0.3
+  outer-angle: 0.5
+  intensity: [ 1, 1, 1 ]
+",
+        )
+        .unwrap();
+        let lights = &s.world.lights;
+        assert_eq!(lights.len(), 4);
+        assert!(matches!(&lights[0], Light::Point(l) if l.position == Tuple::point(50.0, 100.0, -50.0)));
+        match &lights[1] {
+            Light::Area(l) => {
+                assert_eq!(l.samples(), 100);
+                assert_eq!(l.intensity, Color::color(1.5, 1.5, 1.5));
+            }
+            other => panic!("expected an area light, got {:?}", other),
+        }
+        assert!(matches!(&lights[2], Light::Directional(l) if l.direction == Tuple::vector(0.0, -1.0, 0.0)));
+        assert!(matches!(&lights[3], Light::Spot(_)));
+    }
+
+    #[test]
+    fn test_jittered_light_render() {
+        // the jittered light's soft shadow comes out the same however many threads render it
+        let s = scene(
+            "
+- add: light
+  corner: [ -1, 4, -1 ]
+  uvec: [ 2, 0, 0 ]
+  vvec: [ 0, 0, 2 ]
+  usteps: 3
+  vsteps: 3
+  jitter: true
+  intensity: [ 1, 1, 1 ]
+- add: plane
+- add: sphere
+  transform:
+    - [ translate, 0, 1, 0 ]
+",
+        )
+        .unwrap();
+        let mut camera = s.camera.clone();
+        camera.set_size(20, 10);
+        camera.threads = 1;
+        let expected = camera.render(&s.world);
+        camera.threads = 4;
+        camera.tile_size = 3;
+        assert_eq!(camera.render(&s.world).data, expected.data);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let s = scene(
+            "
+- add: plane
+- add: cube
+- add: cylinder
+  min: -1
+  max: 2
+  closed: true
+- add: cone
+  min: -1
+  max: 0
+- add: group
+  children:
+    - add: sphere
+      transform:
+        - [ translate, 3, 0, 0 ]
+    - add: sphere
+- add: csg
+  operation: difference
+  left:
+    add: cube
+  right:
+    add: sphere
+    transform:
+      - [ scale, 1.5, 1.5, 1.5 ]
+",
+        )
+        .unwrap();
+        let objects = &s.world.objects;
+        assert_eq!(objects.len(), 6);
+        let b = objects[2].bounds();
+        assert_eq!((b.min.y, b.max.y), (-1.0, 2.0));
+        let b = objects[3].bounds();
+        assert_eq!((b.min, b.max), (Tuple::point(-1.0, -1.0, -1.0), Tuple::point(1.0, 0.0, 1.0)));
+        let b = objects[4].bounds();
+        assert_eq!((b.min.x, b.max.x), (-1.0, 4.0));
+    }
+
+    #[test]
+    fn test_transform_order() {
+        let s = scene(
+            "
+- add: sphere
+  transform:
+    - [ rotate-x, 1.5707963267948966 ]
+    - [ scale, 5, 5, 5 ]
+    - [ translate, 10, 5, 7 ]
+- add: sphere
+  transform:
+    - [ shear, 1, 0, 0, 0, 0, 0 ]
+    - [ rotate-y, 0.5 ]
+    - [ rotate-z, 0.25 ]
+",
+        )
+        .unwrap();
+        let expected = &(&translation(10.0, 5.0, 7.0) * &scaling(5.0, 5.0, 5.0)) * &rotation_x(PI / 2.0);
+        assert_eq!(*s.world.objects[0].transform(), expected);
+        let expected =
+            &(&rotation_z(0.25) * &rotation_y(0.5)) * &shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(*s.world.objects[1].transform(), expected);
+    }
+
+    #[test]
+    fn test_materials() {
+        let s = scene(
+            "
+- add: sphere
+  material:
+    color: [ 1, 0.2, 1 ]
+    ambient: 0.2
+    diffuse: 0.7
+    specular: 0.1
+    shininess: 50
+    reflective: 0.3
+    transparency: 0.9
+    refractive-index: 1.5
+",
+        )
+        .unwrap();
+        let m = s.world.objects[0].material();
+        assert_eq!(m.color, Color::color(1.0, 0.2, 1.0));
+        assert_eq!((m.ambient, m.diffuse, m.specular, m.shininess), (0.2, 0.7, 0.1, 50.0));
+        assert_eq!((m.reflective, m.transparency, m.refractive_index), (0.3, 0.9, 1.5));
+        assert!(m.pattern.is_none());
+    }
+
+    #[test]
+    fn test_define_and_extend() {
+        let s = scene(
+            "
+- define: white-material
+  value:
+    color: [ 1, 1, 1 ]
+    diffuse: 0.7
+    ambient: 0.1
+- define: blue-material
+  extend: white-material
+  value:
+    color: [ 0.537, 0.831, 0.914 ]
+- define: standard-transform
+  value:
+    - [ translate, 1, -1, 1 ]
+    - [ scale, 0.5, 0.5, 0.5 ]
+- define: large-object
+  value:
+    - standard-transform
+    - [ scale, 3.5, 3.5, 3.5 ]
+- define: blue-cube
+  value:
+    add: cube
+    material: blue-material
+    transform:
+      - large-object
+- add: blue-cube
+- add: blue-cube
+  material: white-material
+",
+        )
+        .unwrap();
+        let blue = s.world.objects[0].material();
+        assert_eq!(blue.color, Color::color(0.537, 0.831, 0.914));
+        assert_eq!((blue.diffuse, blue.ambient), (0.7, 0.1));
+        let expected =
+            &(&scaling(3.5, 3.5, 3.5) * &scaling(0.5, 0.5, 0.5)) * &translation(1.0, -1.0, 1.0);
+        assert_eq!(*s.world.objects[0].transform(), expected);
+        // keys given with `add` override the definition's
+        assert_eq!(s.world.objects[1].material().color, Color::color(1.0, 1.0, 1.0));
+        assert_eq!(*s.world.objects[1].transform(), expected);
+    }
+
+    #[test]
+    fn test_patterns() {
+        let s = scene(
+            "
+- add: plane
+  material:
+    pattern:
+      type: stripes
+      colors:
+        - [ 1, 1, 1 ]
+        - [ 0, 0, 0 ]
+      transform:
+        - [ scale, 2, 2, 2 ]
+- add: sphere
+  material:
+    pattern:
+      type: map
+      mapping: spherical
+      uv_pattern:
+        type: checkers
+        width: 16
+        height: 8
+        colors:
+          - [ 0, 0.5, 0 ]
+          - [ 1, 1, 1 ]
+",
+        )
+        .unwrap();
+        let stripes = s.world.objects[0].material().pattern.clone().unwrap();
+        assert_eq!(stripes.pattern_at(Tuple::point(1.5, 0.0, 0.0)), Color::color(1.0, 1.0, 1.0));
+        assert_eq!(stripes.pattern_at(Tuple::point(2.5, 0.0, 0.0)), Color::color(0.0, 0.0, 0.0));
+        assert!(s.world.objects[1].material().pattern.is_some());
+
+        // one plain face per side of the cube, each a shade of red
+        let faces = ["left", "front", "right", "back", "up", "down"];
+        let mut items = "- add: cube\n  material:\n    pattern:\n      type: map\n      mapping: cube\n".to_string();
+        for (i, face) in faces.iter().enumerate() {
+            let red = i as f64 / 10.0;
+            items += &format!("      {}:\n        type: checkers\n        width: 1\n        height: 1\n", face);
+            items += &format!("        colors:\n          - [ {red}, 0, 0 ]\n          - [ {red}, 0, 0 ]\n");
+        }
+        let s = scene(&items).unwrap();
+        let cube = s.world.objects[0].material().pattern.clone().unwrap();
+        for (i, (x, y, z)) in
+            [(-1.0, 0.5, 0.5), (0.5, 0.5, 1.0), (1.0, 0.5, 0.5), (0.5, 0.5, -1.0), (0.5, 1.0, 0.5), (0.5, -1.0, 0.5)]
+                .into_iter()
+                .enumerate()
+        {
+            assert_eq!(cube.pattern_at(Tuple::point(x, y, z)), Color::color(i as f64 / 10.0, 0.0, 0.0), "{}", faces[i]);
+        }
+        assert_eq!(
+            error(&items.replace("      down:", "      bottom:")),
+            (12, "[1].material.pattern".to_string(), SceneErrorKind::MissingKey("down".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_anchors() {
+        let s = scene(
+            "
+- add: sphere
+  material: &shiny
+    color: [ 1, 0, 0 ]
+    reflective: 0.5
+- add: cube
+  material: *shiny
+",
+        )
+        .unwrap();
+        assert_eq!(s.world.objects[1].material().reflective, 0.5);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error("\n- add: sphere\n  material:\n    colour: [ 1, 0, 0 ]\n"),
+            (12, "[1].material.colour".to_string(), SceneErrorKind::UnknownKey("colour".to_string()))
+        );
+        assert_eq!(
+            error("- add: sphere\n  transform:\n    - [ translate, 1, 2 ]\n"),
+            (
+                11,
+                "[1].transform[0]".to_string(),
+                SceneErrorKind::Expected("translate followed by 3 numbers".to_string())
+            )
+        );
+        assert_eq!(
+            error("- add: light\n  at: [ 1, x, 2 ]\n  intensity: [ 1, 1, 1 ]\n"),
+            (10, "[1].at[1]".to_string(), SceneErrorKind::Expected("a number".to_string()))
+        );
+        assert_eq!(
+            error("- add: teapot\n"),
+            (9, "[1].add".to_string(), SceneErrorKind::UnknownName("teapot".to_string()))
+        );
+        assert_eq!(
+            error("- add: light\n  at: [ 1, 1, 2 ]\n"),
+            (9, "[1]".to_string(), SceneErrorKind::MissingKey("intensity".to_string()))
+        );
+        // the line is that of the definition referring to itself
+        assert_eq!(
+            error("- define: loop\n  value:\n    add: loop\n- add: loop\n"),
+            (
+                11,
+                "[2].add".to_string(),
+                SceneErrorKind::Expected("definitions that do not refer to themselves".to_string())
+            )
+        );
+        let (line, path, kind) = error("- add: sphere\n  material: [ 1, 2\n");
+        assert_eq!((line, path.as_str()), (11, ""));
+        assert!(matches!(kind, SceneErrorKind::Syntax(_)));
+
+        assert_eq!(
+            error("- add: sphere\n  transform:\n    - [ scale, 0, 1, 1 ]\n"),
+            (11, "[1].transform".to_string(), SceneErrorKind::Expected("an invertible transformation".to_string()))
+        );
+        let pattern = "- add: plane\n  material:\n    pattern:\n      type: stripes\n      colors:\n        \
+                       - [ 1, 1, 1 ]\n        - [ 0, 0, 0 ]\n      transform:\n        - [ shear, 1, 0, 1, 0, 0, 0 ]\n        \
+                       - [ scale, 1, 1, 0 ]\n";
+        assert_eq!(
+            error(pattern),
+            (
+                17,
+                "[1].material.pattern.transform".to_string(),
+                SceneErrorKind::Expected("an invertible transformation".to_string())
+            )
+        );
+
+        let e = parse_scene("- add: sphere\n").unwrap_err();
+        assert_eq!(e.kind, SceneErrorKind::MissingCamera);
+        let e = scene("- add: sphere\n  material:\n    shininess: high\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 11: [1].material.shininess: expected a number");
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir().join(format!("ray-scene-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("triangle.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        let file = dir.join("scene.yml");
+        fs::write(&file, format!("{}- add: obj\n  file: triangle.obj\n", CAMERA)).unwrap();
+        let s = load_scene(&file).unwrap();
+        let b = s.world.objects[0].bounds();
+        assert_eq!((b.min, b.max), (Tuple::point(0.0, 0.0, 0.0), Tuple::point(1.0, 1.0, 0.0)));
+
+        fs::write(&file, format!("{}- add: obj\n  file: missing.obj\n", CAMERA)).unwrap();
+        let e = load_scene(&file).unwrap_err();
+        assert_eq!((e.line, e.path.as_str()), (10, "[1].file"));
+        assert!(matches!(e.kind, SceneErrorKind::File(_)));
+
+        fs::write(dir.join("empty.ppm"), "P3\n0 0\n255\n").unwrap();
+        let texture = "- add: sphere\n  material:\n    pattern:\n      type: map\n      mapping: spherical\n      \
+                       uv_pattern:\n        type: image\n        file: empty.ppm\n";
+        fs::write(&file, format!("{}{}", CAMERA, texture)).unwrap();
+        let e = load_scene(&file).unwrap_err();
+        assert_eq!(e.path, "[1].material.pattern.uv_pattern.file");
+        assert_eq!(e.kind, SceneErrorKind::Expected("an image with at least one pixel".to_string()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_example_scene() {
+        let s = parse_scene(include_str!("../scenes/spheres.yml")).unwrap();
+        assert_eq!((s.camera.hsize(), s.camera.vsize()), (900, 550));
+        assert_eq!(s.world.lights.len(), 1);
+        assert_eq!(s.world.objects.len(), 4);
+        let right = &s.world.objects[2];
+        assert_eq!(right.material().color, Color::color(0.5, 1.0, 0.1));
+        assert_eq!(right.material().diffuse, 0.7);
+        assert_eq!(*right.transform(), &translation(1.5, 0.5, -0.5) * &scaling(0.5, 0.5, 0.5));
+    }
+}
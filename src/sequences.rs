@@ -0,0 +1,83 @@
+use std::fmt::Debug;
+
+/// A source of numbers in 0..1, used to jitter sample positions. Sequences
+/// are owned by whoever draws from them, e.g. one per pixel, so that what
+/// they return never depends on the order pixels are rendered in.
+pub trait Sequence: Debug {
+    fn next(&mut self) -> f64;
+}
+
+/// Cycles through a fixed list of numbers, for repeatable sampling and tests.
+#[derive(Debug)]
+pub struct CyclicSequence {
+    values: Vec<f64>,
+    index: usize,
+}
+
+impl CyclicSequence {
+    pub fn cyclic_sequence(values: Vec<f64>) -> CyclicSequence {
+        assert!(!values.is_empty(), "a sequence needs at least one value");
+        CyclicSequence { values, index: 0 }
+    }
+}
+
+impl Sequence for CyclicSequence {
+    fn next(&mut self) -> f64 {
+        let v = self.values[self.index];
+        self.index = (self.index + 1) % self.values.len();
+        v
+    }
+}
+
+/// Pseudo-random numbers (SplitMix64), reproducible for a given seed.
+#[derive(Debug)]
+pub struct RandomSequence {
+    state: u64,
+}
+
+impl RandomSequence {
+    pub fn random_sequence(seed: u64) -> RandomSequence {
+        RandomSequence { state: seed }
+    }
+}
+
+impl Sequence for RandomSequence {
+    fn next(&mut self) -> f64 {
+        const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+        self.state = self.state.wrapping_add(GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // the top 53 bits fill an f64 mantissa exactly
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cyclic_sequence() {
+        let mut gen = CyclicSequence::cyclic_sequence(vec![0.1, 0.5, 1.0]);
+        let values: Vec<f64> = (0..4).map(|_| gen.next()).collect();
+        assert_eq!(values, vec![0.1, 0.5, 1.0, 0.1]);
+    }
+
+    #[test]
+    fn test_random_sequence() {
+        let mut a = RandomSequence::random_sequence(42);
+        let mut b = RandomSequence::random_sequence(42);
+        let mut sum = 0.0;
+        for _ in 0..1000 {
+            let v = a.next();
+            assert!((0.0..1.0).contains(&v));
+            assert_eq!(v, b.next());
+            sum += v;
+        }
+        // roughly uniform
+        assert!((sum / 1000.0 - 0.5).abs() < 0.05);
+        assert_ne!(RandomSequence::random_sequence(1).next(), RandomSequence::random_sequence(2).next());
+    }
+}
@@ -0,0 +1,315 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::materials::Material;
+use crate::matrices::Matrix;
+use crate::rays::Ray;
+use crate::tuples::Tuple;
+
+/// State every shape carries: its transform, with the inverses needed to move
+/// rays and normals between world and object space, its material, and the
+/// chain of groups it is nested in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeProps {
+    pub material: Material,
+    transform: Matrix,
+    inverse: Matrix,
+    inverse_transpose: Matrix,
+    parent: Option<Arc<Parent>>,
+}
+
+/// One link in the chain of groups enclosing a shape. Links are shared by
+/// all the children of a group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parent {
+    inverse: Matrix,
+    inverse_transpose: Matrix,
+    parent: Option<Arc<Parent>>,
+}
+
+impl Parent {
+    /// The link a group with these props hands down to its children.
+    pub fn parent(props: &ShapeProps) -> Parent {
+        Parent {
+            inverse: props.inverse.clone(),
+            inverse_transpose: props.inverse_transpose.clone(),
+            parent: props.parent.clone(),
+        }
+    }
+
+    /// Converts a point from world space into the space of this group's children.
+    pub fn world_to_object(&self, point: Tuple) -> Tuple {
+        let point = match &self.parent {
+            Some(p) => p.world_to_object(point),
+            None => point,
+        };
+        &self.inverse * point
+    }
+
+    /// Converts a normal from the space of this group's children into world space.
+    pub fn normal_to_world(&self, normal: Tuple) -> Tuple {
+        let mut n = &self.inverse_transpose * normal;
+        n.w = 0.0;
+        let n = n.normalize();
+        match &self.parent {
+            Some(p) => p.normal_to_world(n),
+            None => n,
+        }
+    }
+}
+
+impl ShapeProps {
+    pub fn shape_props() -> ShapeProps {
+        ShapeProps {
+            transform: Matrix::identity(),
+            material: Material::material(),
+            inverse: Matrix::identity(),
+            inverse_transpose: Matrix::identity(),
+            parent: None,
+        }
+    }
+
+    /// Sets the transform, caching its inverses. Panics if the matrix is not invertible.
+    ///
+    /// Outside the crate, transforms and parents are only changed through
+    /// `Shape::set_transform` and `Shape::set_parent`, which groups override
+    /// to keep their children in sync.
+    pub(crate) fn set_transform(&mut self, m: Matrix) {
+        let inverse = m.inverse().expect("shape transform must be invertible");
+        self.inverse_transpose = inverse.transpose();
+        self.inverse = inverse;
+        self.transform = m;
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.transform
+    }
+
+    pub fn inverse(&self) -> &Matrix {
+        &self.inverse
+    }
+
+    pub fn inverse_transpose(&self) -> &Matrix {
+        &self.inverse_transpose
+    }
+
+    pub fn parent(&self) -> Option<&Arc<Parent>> {
+        self.parent.as_ref()
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.parent = parent;
+    }
+}
+
+impl Default for ShapeProps {
+    fn default() -> ShapeProps {
+        ShapeProps::shape_props()
+    }
+}
+
+/// A primitive that can be placed in a `World`.
+///
+/// Implementors only deal with object space through `local_intersect` and
+/// `local_normal_at`; the provided `intersect` and `normal_at` take care of
+/// applying the shape's transform and those of the groups it belongs to.
+/// Shapes are `Any`, so a `&dyn Shape` can be downcast to its concrete type.
+pub trait Shape: Any + Debug + Send + Sync {
+    fn props(&self) -> &ShapeProps;
+
+    fn props_mut(&mut self) -> &mut ShapeProps;
+
+    /// Intersects a ray that has already been transformed into object space.
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_>;
+
+    /// The normal at a point given in object space.
+    fn local_normal_at(&self, point: Tuple) -> Tuple;
+
+    /// The box enclosing the shape in object space.
+    fn bounds(&self) -> BoundingBox;
+
+    /// The box enclosing the shape in the space of its group, i.e. after its
+    /// own transform is applied.
+    fn parent_space_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.transform())
+    }
+
+    /// Reorganizes groups into a bounding volume hierarchy, so that no group
+    /// directly holds `threshold` or more children that could be split up.
+    /// Does nothing for primitives.
+    fn divide(&mut self, _threshold: usize) {}
+
+    /// Whether `other` is this shape or, for groups and CSG shapes, one of
+    /// the shapes nested in it.
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+
+    /// Like `local_normal_at`, for shapes whose normal depends on where
+    /// exactly they were hit (e.g. the `u`/`v` of a smooth triangle).
+    fn local_normal_at_hit(&self, point: Tuple, _hit: &Intersection) -> Tuple {
+        self.local_normal_at(point)
+    }
+
+    fn transform(&self) -> &Matrix {
+        self.props().transform()
+    }
+
+    fn set_transform(&mut self, m: Matrix) {
+        self.props_mut().set_transform(m);
+    }
+
+    /// Called by groups when the shape is added to them, or when the
+    /// transforms above the shape change.
+    fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.props_mut().set_parent(parent);
+    }
+
+    fn material(&self) -> &Material {
+        &self.props().material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.props_mut().material
+    }
+
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        self.local_intersect(&ray.transform(self.props().inverse()))
+    }
+
+    fn normal_at(&self, world_point: Tuple) -> Tuple {
+        let local_point = self.world_to_object(world_point);
+        self.normal_to_world(self.local_normal_at(local_point))
+    }
+
+    fn normal_at_hit(&self, world_point: Tuple, hit: &Intersection) -> Tuple {
+        let local_point = self.world_to_object(world_point);
+        self.normal_to_world(self.local_normal_at_hit(local_point, hit))
+    }
+
+    /// Converts a world space point into object space, walking down from the
+    /// outermost enclosing group.
+    fn world_to_object(&self, point: Tuple) -> Tuple {
+        let point = match self.props().parent() {
+            Some(p) => p.world_to_object(point),
+            None => point,
+        };
+        self.props().inverse() * point
+    }
+
+    /// Converts an object space normal into world space, walking up through
+    /// the enclosing groups.
+    fn normal_to_world(&self, normal: Tuple) -> Tuple {
+        let mut world_normal = self.props().inverse_transpose() * normal;
+        world_normal.w = 0.0;
+        let world_normal = world_normal.normalize();
+        match self.props().parent() {
+            Some(p) => p.normal_to_world(world_normal),
+            None => world_normal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformations::{rotation_z, scaling, translation};
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct TestShape {
+        props: ShapeProps,
+        saved_ray: Mutex<Option<Ray>>,
+    }
+
+    impl Shape for TestShape {
+        fn props(&self) -> &ShapeProps {
+            &self.props
+        }
+
+        fn props_mut(&mut self) -> &mut ShapeProps {
+            &mut self.props
+        }
+
+        fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+            *self.saved_ray.lock().unwrap() = Some(*ray);
+            Intersections::default()
+        }
+
+        fn local_normal_at(&self, point: Tuple) -> Tuple {
+            Tuple::vector(point.x, point.y, point.z)
+        }
+
+        fn bounds(&self) -> BoundingBox {
+            BoundingBox::bounding_box(Tuple::point(-1.0, -1.0, -1.0), Tuple::point(1.0, 1.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn test_default_props() {
+        let s = TestShape::default();
+        assert_eq!(*s.transform(), Matrix::identity());
+        assert_eq!(*s.material(), Material::material());
+    }
+
+    #[test]
+    fn test_set_props() {
+        let mut s = TestShape::default();
+        s.set_transform(translation(2.0, 3.0, 4.0));
+        assert_eq!(*s.transform(), translation(2.0, 3.0, 4.0));
+
+        s.material_mut().ambient = 1.0;
+        assert!(s.material().ambient == 1.0);
+    }
+
+    #[test]
+    fn test_intersect_scaled() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let mut s = TestShape::default();
+        s.set_transform(scaling(2.0, 2.0, 2.0));
+        s.intersect(&r);
+        let saved = s.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(saved.origin, Tuple::point(0.0, 0.0, -2.5));
+        assert_eq!(saved.direction, Tuple::vector(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_intersect_translated() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let mut s = TestShape::default();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        s.intersect(&r);
+        let saved = s.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(saved.origin, Tuple::point(-5.0, 0.0, -5.0));
+        assert_eq!(saved.direction, Tuple::vector(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_normal_translated() {
+        let mut s = TestShape::default();
+        s.set_transform(translation(0.0, 1.0, 0.0));
+        let n = s.normal_at(Tuple::point(0.0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        assert!(n.approx_eq(&Tuple::vector(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2)));
+    }
+
+    #[test]
+    fn test_parent_space_bounds() {
+        let mut s = TestShape::default();
+        s.set_transform(&translation(1.0, -3.0, 5.0) * &scaling(0.5, 2.0, 4.0));
+        let b = s.parent_space_bounds();
+        assert_eq!(b.min, Tuple::point(0.5, -5.0, 1.0));
+        assert_eq!(b.max, Tuple::point(1.5, -1.0, 9.0));
+    }
+
+    #[test]
+    fn test_normal_transformed() {
+        let mut s = TestShape::default();
+        s.set_transform(&scaling(1.0, 0.5, 1.0) * &rotation_z(PI / 5.0));
+        let n = s.normal_at(Tuple::point(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        assert!(n.approx_eq(&Tuple::vector(0.0, 0.97014, -0.24254)));
+    }
+}
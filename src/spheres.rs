@@ -0,0 +1,191 @@
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::rays::Ray;
+use crate::shapes::{Shape, ShapeProps};
+use crate::tuples::Tuple;
+
+/// A unit sphere centered at the origin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sphere {
+    pub props: ShapeProps,
+}
+
+impl Sphere {
+    pub fn sphere() -> Sphere {
+        Sphere {
+            props: ShapeProps::shape_props(),
+        }
+    }
+
+    /// A transparent sphere with the refractive index of glass.
+    pub fn glass_sphere() -> Sphere {
+        let mut s = Sphere::sphere();
+        s.props.material.transparency = 1.0;
+        s.props.material.refractive_index = 1.5;
+        s
+    }
+}
+
+impl Default for Sphere {
+    fn default() -> Sphere {
+        Sphere::sphere()
+    }
+}
+
+impl Shape for Sphere {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let sphere_to_ray = ray.origin - Tuple::point(0.0, 0.0, 0.0);
+
+        let a = ray.direction.dot(&ray.direction);
+        let b = 2.0 * ray.direction.dot(&sphere_to_ray);
+        let c = sphere_to_ray.dot(&sphere_to_ray) - 1.0;
+        let discriminant = b * b - 4.0 * a * c;
+
+        if discriminant < 0.0 {
+            return Intersections::default();
+        }
+        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+        Intersections::intersections(vec![
+            Intersection::intersection(t1, self),
+            Intersection::intersection(t2, self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        point - Tuple::point(0.0, 0.0, 0.0)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::bounding_box(Tuple::point(-1.0, -1.0, -1.0), Tuple::point(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::Matrix;
+    use crate::transformations::{rotation_z, scaling, translation};
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    #[test]
+    fn test_intersect_two_points() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::sphere();
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == 4.0);
+        assert!(xs[1].t == 6.0);
+        assert!(std::ptr::addr_eq(xs[0].object, &s));
+        assert!(std::ptr::addr_eq(xs[1].object, &s));
+    }
+
+    #[test]
+    fn test_intersect_tangent() {
+        let r = Ray::ray(Tuple::point(0.0, 1.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::sphere();
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == 5.0);
+        assert!(xs[1].t == 5.0);
+    }
+
+    #[test]
+    fn test_intersect_miss() {
+        let r = Ray::ray(Tuple::point(0.0, 2.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(Sphere::sphere().intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn test_intersect_inside() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::sphere();
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == -1.0);
+        assert!(xs[1].t == 1.0);
+    }
+
+    #[test]
+    fn test_intersect_behind() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::sphere();
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == -6.0);
+        assert!(xs[1].t == -4.0);
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut s = Sphere::sphere();
+        assert_eq!(*s.transform(), Matrix::identity());
+        let t = translation(2.0, 3.0, 4.0);
+        s.set_transform(t.clone());
+        assert_eq!(*s.transform(), t);
+    }
+
+    #[test]
+    fn test_intersect_transformed() {
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+
+        let mut s = Sphere::sphere();
+        s.set_transform(scaling(2.0, 2.0, 2.0));
+        let xs = s.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t == 3.0);
+        assert!(xs[1].t == 7.0);
+
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        assert!(s.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let s = Sphere::sphere();
+        assert_eq!(s.normal_at(Tuple::point(1.0, 0.0, 0.0)), Tuple::vector(1.0, 0.0, 0.0));
+        assert_eq!(s.normal_at(Tuple::point(0.0, 1.0, 0.0)), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(s.normal_at(Tuple::point(0.0, 0.0, 1.0)), Tuple::vector(0.0, 0.0, 1.0));
+
+        let v = 3.0_f64.sqrt() / 3.0;
+        let n = s.normal_at(Tuple::point(v, v, v));
+        assert!(n.approx_eq(&Tuple::vector(v, v, v)));
+        assert!(n.approx_eq(&n.normalize()));
+    }
+
+    #[test]
+    fn test_normal_at_transformed() {
+        let mut s = Sphere::sphere();
+        s.set_transform(translation(0.0, 1.0, 0.0));
+        let n = s.normal_at(Tuple::point(0.0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        assert!(n.approx_eq(&Tuple::vector(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2)));
+
+        s.set_transform(&scaling(1.0, 0.5, 1.0) * &rotation_z(PI / 5.0));
+        let h = 2.0_f64.sqrt() / 2.0;
+        let n = s.normal_at(Tuple::point(0.0, h, -h));
+        assert!(n.approx_eq(&Tuple::vector(0.0, 0.97014, -0.24254)));
+    }
+
+    #[test]
+    fn test_glass_sphere() {
+        let s = Sphere::glass_sphere();
+        assert_eq!(*s.transform(), Matrix::identity());
+        assert!(s.material().transparency == 1.0);
+        assert!(s.material().refractive_index == 1.5);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = Sphere::sphere().bounds();
+        assert_eq!(b.min, Tuple::point(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Tuple::point(1.0, 1.0, 1.0));
+    }
+}
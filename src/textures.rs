@@ -0,0 +1,521 @@
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::canvas::Canvas;
+use crate::colors::Color;
+use crate::patterns::{Pattern, PatternProps};
+use crate::tuples::Tuple;
+
+/// A 2D texture, addressed by `u` and `v` in 0..1 with `v` pointing up.
+pub trait UvPattern: Debug + Send + Sync {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color;
+}
+
+/// A `width` by `height` checkerboard spread over the unit square.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvCheckers {
+    pub width: f64,
+    pub height: f64,
+    pub a: Color,
+    pub b: Color,
+}
+
+impl UvCheckers {
+    pub fn uv_checkers(width: f64, height: f64, a: Color, b: Color) -> UvCheckers {
+        UvCheckers { width, height, a, b }
+    }
+}
+
+impl UvPattern for UvCheckers {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let u2 = (u * self.width).floor();
+        let v2 = (v * self.height).floor();
+        if (u2 + v2).rem_euclid(2.0) == 0.0 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+/// One color with a different one in each corner, handy for checking that
+/// a texture is oriented the right way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvAlignCheck {
+    pub main: Color,
+    pub ul: Color,
+    pub ur: Color,
+    pub bl: Color,
+    pub br: Color,
+}
+
+impl UvAlignCheck {
+    pub fn uv_align_check(main: Color, ul: Color, ur: Color, bl: Color, br: Color) -> UvAlignCheck {
+        UvAlignCheck { main, ul, ur, bl, br }
+    }
+}
+
+impl UvPattern for UvAlignCheck {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        if v > 0.8 {
+            if u < 0.2 {
+                return self.ul;
+            }
+            if u > 0.8 {
+                return self.ur;
+            }
+        } else if v < 0.2 {
+            if u < 0.2 {
+                return self.bl;
+            }
+            if u > 0.8 {
+                return self.br;
+            }
+        }
+        self.main
+    }
+}
+
+/// An image texture, e.g. one read with `Canvas::from_ppm`.
+#[derive(Debug, Clone)]
+pub struct UvImage {
+    pub canvas: Canvas,
+}
+
+impl UvImage {
+    /// Panics if the image has no pixels.
+    pub fn uv_image(canvas: Canvas) -> UvImage {
+        assert!(canvas.width > 0 && canvas.height > 0, "an image texture needs at least one pixel");
+        UvImage { canvas }
+    }
+}
+
+impl UvPattern for UvImage {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        // images are stored top row first, while v points up
+        let (u, v) = (u.clamp(0.0, 1.0), 1.0 - v.clamp(0.0, 1.0));
+        let x = (u * (self.canvas.width - 1) as f64).round() as usize;
+        let y = (v * (self.canvas.height - 1) as f64).round() as usize;
+        self.canvas.pixel_at(x, y)
+    }
+}
+
+/// Wraps a point on the unit sphere onto the unit square.
+pub fn spherical_map(p: Tuple) -> (f64, f64) {
+    let theta = p.x.atan2(p.z);
+    let radius = Tuple::vector(p.x, p.y, p.z).magnitude();
+    let phi = (p.y / radius).acos();
+    let raw_u = theta / (2.0 * PI);
+    // flip u so it grows counterclockwise when viewed from above
+    let u = 1.0 - (raw_u + 0.5);
+    let v = 1.0 - phi / PI;
+    (u, v)
+}
+
+/// Tiles the xz plane with the unit square.
+pub fn planar_map(p: Tuple) -> (f64, f64) {
+    (p.x.rem_euclid(1.0), p.z.rem_euclid(1.0))
+}
+
+/// Wraps a cylinder around the y axis, repeating `v` every unit of height.
+pub fn cylindrical_map(p: Tuple) -> (f64, f64) {
+    let theta = p.x.atan2(p.z);
+    let raw_u = theta / (2.0 * PI);
+    let u = 1.0 - (raw_u + 0.5);
+    (u, p.y.rem_euclid(1.0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+}
+
+impl UvMapping {
+    pub fn map(&self, p: Tuple) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => spherical_map(p),
+            UvMapping::Planar => planar_map(p),
+            UvMapping::Cylindrical => cylindrical_map(p),
+        }
+    }
+}
+
+/// Projects a `UvPattern` onto 3D space through one of the `UvMapping`s.
+#[derive(Debug)]
+pub struct TextureMapPattern {
+    pub props: PatternProps,
+    pub uv_pattern: Arc<dyn UvPattern>,
+    pub mapping: UvMapping,
+}
+
+impl TextureMapPattern {
+    pub fn texture_map_pattern(uv_pattern: Arc<dyn UvPattern>, mapping: UvMapping) -> TextureMapPattern {
+        TextureMapPattern {
+            props: PatternProps::pattern_props(),
+            uv_pattern,
+            mapping,
+        }
+    }
+}
+
+impl Pattern for TextureMapPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        let (u, v) = self.mapping.map(point);
+        self.uv_pattern.uv_pattern_at(u, v)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+/// The face of the -1..1 cube that `p` lies on.
+pub fn face_from_point(p: Tuple) -> CubeFace {
+    let coord = p.x.abs().max(p.y.abs()).max(p.z.abs());
+    if coord == p.x {
+        CubeFace::Right
+    } else if coord == -p.x {
+        CubeFace::Left
+    } else if coord == p.y {
+        CubeFace::Up
+    } else if coord == -p.y {
+        CubeFace::Down
+    } else if coord == p.z {
+        CubeFace::Front
+    } else {
+        CubeFace::Back
+    }
+}
+
+/// Maps a point on the given face of the -1..1 cube onto the unit square,
+/// as if the cube were unfolded around its front face.
+pub fn cube_map(face: CubeFace, p: Tuple) -> (f64, f64) {
+    let (u, v) = match face {
+        CubeFace::Front => (p.x + 1.0, p.y + 1.0),
+        CubeFace::Back => (1.0 - p.x, p.y + 1.0),
+        CubeFace::Left => (p.z + 1.0, p.y + 1.0),
+        CubeFace::Right => (1.0 - p.z, p.y + 1.0),
+        CubeFace::Up => (p.x + 1.0, 1.0 - p.z),
+        CubeFace::Down => (p.x + 1.0, p.z + 1.0),
+    };
+    ((u / 2.0).rem_euclid(1.0), (v / 2.0).rem_euclid(1.0))
+}
+
+/// A different `UvPattern` on each face of a cube.
+#[derive(Debug)]
+pub struct CubeMapPattern {
+    pub props: PatternProps,
+    pub left: Arc<dyn UvPattern>,
+    pub front: Arc<dyn UvPattern>,
+    pub right: Arc<dyn UvPattern>,
+    pub back: Arc<dyn UvPattern>,
+    pub up: Arc<dyn UvPattern>,
+    pub down: Arc<dyn UvPattern>,
+}
+
+impl CubeMapPattern {
+    pub fn cube_map_pattern(
+        left: Arc<dyn UvPattern>,
+        front: Arc<dyn UvPattern>,
+        right: Arc<dyn UvPattern>,
+        back: Arc<dyn UvPattern>,
+        up: Arc<dyn UvPattern>,
+        down: Arc<dyn UvPattern>,
+    ) -> CubeMapPattern {
+        CubeMapPattern {
+            props: PatternProps::pattern_props(),
+            left,
+            front,
+            right,
+            back,
+            up,
+            down,
+        }
+    }
+}
+
+impl Pattern for CubeMapPattern {
+    fn props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+
+    fn local_pattern_at(&self, point: Tuple) -> Color {
+        let face = face_from_point(point);
+        let (u, v) = cube_map(face, point);
+        let pattern = match face {
+            CubeFace::Left => &self.left,
+            CubeFace::Front => &self.front,
+            CubeFace::Right => &self.right,
+            CubeFace::Back => &self.back,
+            CubeFace::Up => &self.up,
+            CubeFace::Down => &self.down,
+        };
+        pattern.uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    fn black() -> Color {
+        Color::color(0.0, 0.0, 0.0)
+    }
+
+    fn white() -> Color {
+        Color::color(1.0, 1.0, 1.0)
+    }
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        let near = |a: f64, b: f64| (a - b).abs() < 0.0001;
+        assert!(near(actual.0, expected.0) && near(actual.1, expected.1), "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_uv_checkers() {
+        let c = UvCheckers::uv_checkers(2.0, 2.0, black(), white());
+        let cases = [
+            (0.0, 0.0, black()),
+            (0.5, 0.0, white()),
+            (0.0, 0.5, white()),
+            (0.5, 0.5, black()),
+            (1.0, 1.0, black()),
+        ];
+        for (u, v, expected) in cases {
+            assert_eq!(c.uv_pattern_at(u, v), expected);
+        }
+    }
+
+    #[test]
+    fn test_spherical_map() {
+        let h = FRAC_1_SQRT_2;
+        let cases = [
+            (Tuple::point(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Tuple::point(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Tuple::point(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (Tuple::point(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (Tuple::point(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Tuple::point(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (Tuple::point(h, h, 0.0), (0.25, 0.75)),
+        ];
+        for (p, expected) in cases {
+            assert_uv(spherical_map(p), expected);
+        }
+    }
+
+    #[test]
+    fn test_texture_map_pattern() {
+        let checkers = UvCheckers::uv_checkers(16.0, 8.0, black(), white());
+        let p = TextureMapPattern::texture_map_pattern(Arc::new(checkers), UvMapping::Spherical);
+        let cases = [
+            (Tuple::point(0.4315, 0.4670, 0.7719), white()),
+            (Tuple::point(-0.9654, 0.2552, -0.0534), black()),
+            (Tuple::point(0.1039, 0.7090, 0.6975), white()),
+            (Tuple::point(-0.4986, -0.7856, -0.3663), black()),
+            (Tuple::point(-0.0317, -0.9395, 0.3411), black()),
+            (Tuple::point(0.4809, -0.7721, 0.4154), black()),
+            (Tuple::point(0.0285, -0.9612, -0.2745), black()),
+            (Tuple::point(-0.5734, -0.2162, -0.7903), white()),
+            (Tuple::point(0.7688, -0.1470, 0.6223), black()),
+            (Tuple::point(-0.7652, 0.2175, 0.6060), black()),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(p.pattern_at(point), expected);
+        }
+    }
+
+    #[test]
+    fn test_planar_map() {
+        let cases = [
+            (Tuple::point(0.25, 0.0, 0.5), (0.25, 0.5)),
+            (Tuple::point(0.25, 0.0, -0.25), (0.25, 0.75)),
+            (Tuple::point(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (Tuple::point(1.25, 0.0, 0.5), (0.25, 0.5)),
+            (Tuple::point(0.25, 0.0, -1.75), (0.25, 0.25)),
+            (Tuple::point(1.0, 0.0, -1.0), (0.0, 0.0)),
+            (Tuple::point(0.0, 0.0, 0.0), (0.0, 0.0)),
+        ];
+        for (p, expected) in cases {
+            assert_uv(planar_map(p), expected);
+        }
+    }
+
+    #[test]
+    fn test_cylindrical_map() {
+        let h = FRAC_1_SQRT_2;
+        let cases = [
+            (Tuple::point(0.0, 0.0, -1.0), (0.0, 0.0)),
+            (Tuple::point(0.0, 0.5, -1.0), (0.0, 0.5)),
+            (Tuple::point(0.0, 1.0, -1.0), (0.0, 0.0)),
+            (Tuple::point(h, 0.5, -h), (0.125, 0.5)),
+            (Tuple::point(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (Tuple::point(h, 0.5, h), (0.375, 0.5)),
+            (Tuple::point(0.0, -0.25, 1.0), (0.5, 0.75)),
+            (Tuple::point(-h, 0.5, h), (0.625, 0.5)),
+            (Tuple::point(-1.0, 1.25, 0.0), (0.75, 0.25)),
+            (Tuple::point(-h, 0.5, -h), (0.875, 0.5)),
+        ];
+        for (p, expected) in cases {
+            assert_uv(cylindrical_map(p), expected);
+        }
+    }
+
+    fn align_check() -> UvAlignCheck {
+        UvAlignCheck::uv_align_check(
+            Color::color(1.0, 1.0, 1.0),
+            Color::color(1.0, 0.0, 0.0),
+            Color::color(1.0, 1.0, 0.0),
+            Color::color(0.0, 1.0, 0.0),
+            Color::color(0.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn test_align_check() {
+        let p = align_check();
+        assert_eq!(p.uv_pattern_at(0.5, 0.5), p.main);
+        assert_eq!(p.uv_pattern_at(0.1, 0.9), p.ul);
+        assert_eq!(p.uv_pattern_at(0.9, 0.9), p.ur);
+        assert_eq!(p.uv_pattern_at(0.1, 0.1), p.bl);
+        assert_eq!(p.uv_pattern_at(0.9, 0.1), p.br);
+    }
+
+    #[test]
+    fn test_face_from_point() {
+        let cases = [
+            (Tuple::point(-1.0, 0.5, -0.25), CubeFace::Left),
+            (Tuple::point(1.1, -0.75, 0.8), CubeFace::Right),
+            (Tuple::point(0.1, 0.6, 0.9), CubeFace::Front),
+            (Tuple::point(-0.7, 0.0, -2.0), CubeFace::Back),
+            (Tuple::point(0.5, 1.0, 0.9), CubeFace::Up),
+            (Tuple::point(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+        for (p, expected) in cases {
+            assert_eq!(face_from_point(p), expected);
+        }
+    }
+
+    #[test]
+    fn test_cube_map() {
+        let cases = [
+            (CubeFace::Front, Tuple::point(-0.5, 0.5, 1.0), (0.25, 0.75)),
+            (CubeFace::Front, Tuple::point(0.5, -0.5, 1.0), (0.75, 0.25)),
+            (CubeFace::Back, Tuple::point(0.5, 0.5, -1.0), (0.25, 0.75)),
+            (CubeFace::Back, Tuple::point(-0.5, -0.5, -1.0), (0.75, 0.25)),
+            (CubeFace::Left, Tuple::point(-1.0, 0.5, -0.5), (0.25, 0.75)),
+            (CubeFace::Left, Tuple::point(-1.0, -0.5, 0.5), (0.75, 0.25)),
+            (CubeFace::Right, Tuple::point(1.0, 0.5, 0.5), (0.25, 0.75)),
+            (CubeFace::Right, Tuple::point(1.0, -0.5, -0.5), (0.75, 0.25)),
+            (CubeFace::Up, Tuple::point(-0.5, 1.0, -0.5), (0.25, 0.75)),
+            (CubeFace::Up, Tuple::point(0.5, 1.0, 0.5), (0.75, 0.25)),
+            (CubeFace::Down, Tuple::point(-0.5, -1.0, 0.5), (0.25, 0.75)),
+            (CubeFace::Down, Tuple::point(0.5, -1.0, -0.5), (0.75, 0.25)),
+        ];
+        for (face, p, expected) in cases {
+            assert_uv(cube_map(face, p), expected);
+        }
+    }
+
+    #[test]
+    fn test_cube_map_pattern() {
+        let red = Color::color(1.0, 0.0, 0.0);
+        let yellow = Color::color(1.0, 1.0, 0.0);
+        let brown = Color::color(1.0, 0.5, 0.0);
+        let green = Color::color(0.0, 1.0, 0.0);
+        let cyan = Color::color(0.0, 1.0, 1.0);
+        let blue = Color::color(0.0, 0.0, 1.0);
+        let purple = Color::color(1.0, 0.0, 1.0);
+        let white = white();
+        let face = |main, ul, ur, bl, br| -> Arc<dyn UvPattern> {
+            Arc::new(UvAlignCheck::uv_align_check(main, ul, ur, bl, br))
+        };
+        let p = CubeMapPattern::cube_map_pattern(
+            face(yellow, cyan, red, blue, brown),
+            face(cyan, red, yellow, brown, green),
+            face(red, yellow, purple, green, white),
+            face(green, purple, cyan, white, blue),
+            face(brown, cyan, purple, red, yellow),
+            face(purple, brown, green, blue, white),
+        );
+        let cases = [
+            // left
+            (Tuple::point(-1.0, 0.0, 0.0), yellow),
+            (Tuple::point(-1.0, 0.9, -0.9), cyan),
+            (Tuple::point(-1.0, 0.9, 0.9), red),
+            (Tuple::point(-1.0, -0.9, -0.9), blue),
+            (Tuple::point(-1.0, -0.9, 0.9), brown),
+            // front
+            (Tuple::point(0.0, 0.0, 1.0), cyan),
+            (Tuple::point(-0.9, 0.9, 1.0), red),
+            (Tuple::point(0.9, -0.9, 1.0), green),
+            // right
+            (Tuple::point(1.0, 0.0, 0.0), red),
+            (Tuple::point(1.0, 0.9, 0.9), yellow),
+            // back
+            (Tuple::point(0.0, 0.0, -1.0), green),
+            (Tuple::point(0.9, 0.9, -1.0), purple),
+            // up
+            (Tuple::point(0.0, 1.0, 0.0), brown),
+            (Tuple::point(-0.9, 1.0, -0.9), cyan),
+            // down
+            (Tuple::point(0.0, -1.0, 0.0), purple),
+            (Tuple::point(-0.9, -1.0, 0.9), brown),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(p.pattern_at(point), expected, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn test_uv_image() {
+        // each pixel's gray level is (x + y) / 100
+        let mut canvas = Canvas::canvas(10, 10);
+        for y in 0..10 {
+            for x in 0..10 {
+                let v = (x + y) as f64 / 100.0;
+                canvas.write_pixel(x, y, Color::color(v, v, v));
+            }
+        }
+        let p = UvImage::uv_image(canvas);
+        let cases = [
+            (0.0, 0.0, 0.09),
+            (0.3, 0.0, 0.12),
+            (0.6, 0.3, 0.11),
+            (1.0, 1.0, 0.09),
+        ];
+        for (u, v, expected) in cases {
+            assert!(p.uv_pattern_at(u, v).approx_eq(&Color::color(expected, expected, expected)));
+        }
+        // coordinates outside the unit square stick to the nearest edge
+        assert_eq!(p.uv_pattern_at(-0.5, 2.0), p.uv_pattern_at(0.0, 1.0));
+        assert_eq!(p.uv_pattern_at(1.5, -1.0), p.uv_pattern_at(1.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one pixel")]
+    fn test_uv_image_empty() {
+        UvImage::uv_image(Canvas::canvas(0, 3));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::matrices::Matrix;
+use crate::tuples::Tuple;
+
+pub fn identity() -> Matrix {
+    Matrix::identity()
+}
+
+pub fn translation(x: f64, y: f64, z: f64) -> Matrix {
+    let mut m = Matrix::identity();
+    m[(0, 3)] = x;
+    m[(1, 3)] = y;
+    m[(2, 3)] = z;
+    m
+}
+
+pub fn scaling(x: f64, y: f64, z: f64) -> Matrix {
+    let mut m = Matrix::identity();
+    m[(0, 0)] = x;
+    m[(1, 1)] = y;
+    m[(2, 2)] = z;
+    m
+}
+
+pub fn rotation_x(r: f64) -> Matrix {
+    let mut m = Matrix::identity();
+    m[(1, 1)] = r.cos();
+    m[(1, 2)] = -r.sin();
+    m[(2, 1)] = r.sin();
+    m[(2, 2)] = r.cos();
+    m
+}
+
+pub fn rotation_y(r: f64) -> Matrix {
+    let mut m = Matrix::identity();
+    m[(0, 0)] = r.cos();
+    m[(0, 2)] = r.sin();
+    m[(2, 0)] = -r.sin();
+    m[(2, 2)] = r.cos();
+    m
+}
+
+pub fn rotation_z(r: f64) -> Matrix {
+    let mut m = Matrix::identity();
+    m[(0, 0)] = r.cos();
+    m[(0, 1)] = -r.sin();
+    m[(1, 0)] = r.sin();
+    m[(1, 1)] = r.cos();
+    m
+}
+
+pub fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix {
+    let mut m = Matrix::identity();
+    m[(0, 1)] = xy;
+    m[(0, 2)] = xz;
+    m[(1, 0)] = yx;
+    m[(1, 2)] = yz;
+    m[(2, 0)] = zx;
+    m[(2, 1)] = zy;
+    m
+}
+
+/// Orients the world relative to an eye at `from` looking at `to`.
+pub fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> Matrix {
+    let forward = (to - from).normalize();
+    let left = forward.cross(&up.normalize());
+    let true_up = left.cross(&forward);
+    let orientation = Matrix::from_rows(vec![
+        vec![left.x, left.y, left.z, 0.0],
+        vec![true_up.x, true_up.y, true_up.z, 0.0],
+        vec![-forward.x, -forward.y, -forward.z, 0.0],
+        vec![0.0, 0.0, 0.0, 1.0],
+    ]);
+    orientation * translation(-from.x, -from.y, -from.z)
+}
+
+// Fluent API: each call applies its transformation after the ones already in
+// the chain, so `identity().rotate_x(r).translate(x, y, z)` rotates first.
+impl Matrix {
+    pub fn translate(self, x: f64, y: f64, z: f64) -> Matrix {
+        translation(x, y, z) * self
+    }
+
+    pub fn scale(self, x: f64, y: f64, z: f64) -> Matrix {
+        scaling(x, y, z) * self
+    }
+
+    pub fn rotate_x(self, r: f64) -> Matrix {
+        rotation_x(r) * self
+    }
+
+    pub fn rotate_y(self, r: f64) -> Matrix {
+        rotation_y(r) * self
+    }
+
+    pub fn rotate_z(self, r: f64) -> Matrix {
+        rotation_z(r) * self
+    }
+
+    pub fn shear(self, xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix {
+        shearing(xy, xz, yx, yz, zx, zy) * self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn assert_tuple_eq(a: Tuple, b: Tuple) {
+        assert!(a.approx_eq(&b), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_translation() {
+        let t = translation(5.0, -3.0, 2.0);
+        let p = Tuple::point(-3.0, 4.0, 5.0);
+        assert_eq!(&t * p, Tuple::point(2.0, 1.0, 7.0));
+        assert_eq!(&t.inverse().unwrap() * p, Tuple::point(-8.0, 7.0, 3.0));
+
+        let v = Tuple::vector(-3.0, 4.0, 5.0);
+        assert_eq!(&t * v, v);
+    }
+
+    #[test]
+    fn test_scaling() {
+        let t = scaling(2.0, 3.0, 4.0);
+        assert_eq!(&t * Tuple::point(-4.0, 6.0, 8.0), Tuple::point(-8.0, 18.0, 32.0));
+        assert_eq!(&t * Tuple::vector(-4.0, 6.0, 8.0), Tuple::vector(-8.0, 18.0, 32.0));
+        assert_eq!(
+            &t.inverse().unwrap() * Tuple::vector(-4.0, 6.0, 8.0),
+            Tuple::vector(-2.0, 2.0, 2.0)
+        );
+        // reflection is scaling by a negative value
+        assert_eq!(&scaling(-1.0, 1.0, 1.0) * Tuple::point(2.0, 3.0, 4.0), Tuple::point(-2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let p = Tuple::point(0.0, 1.0, 0.0);
+        let h = 2.0_f64.sqrt() / 2.0;
+        assert_tuple_eq(&rotation_x(PI / 4.0) * p, Tuple::point(0.0, h, h));
+        assert_tuple_eq(&rotation_x(PI / 2.0) * p, Tuple::point(0.0, 0.0, 1.0));
+        assert_tuple_eq(&rotation_x(PI / 4.0).inverse().unwrap() * p, Tuple::point(0.0, h, -h));
+
+        let p = Tuple::point(0.0, 0.0, 1.0);
+        assert_tuple_eq(&rotation_y(PI / 4.0) * p, Tuple::point(h, 0.0, h));
+        assert_tuple_eq(&rotation_y(PI / 2.0) * p, Tuple::point(1.0, 0.0, 0.0));
+
+        let p = Tuple::point(0.0, 1.0, 0.0);
+        assert_tuple_eq(&rotation_z(PI / 4.0) * p, Tuple::point(-h, h, 0.0));
+        assert_tuple_eq(&rotation_z(PI / 2.0) * p, Tuple::point(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_shearing() {
+        let p = Tuple::point(2.0, 3.0, 4.0);
+        assert_eq!(&shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0) * p, Tuple::point(5.0, 3.0, 4.0));
+        assert_eq!(&shearing(0.0, 1.0, 0.0, 0.0, 0.0, 0.0) * p, Tuple::point(6.0, 3.0, 4.0));
+        assert_eq!(&shearing(0.0, 0.0, 1.0, 0.0, 0.0, 0.0) * p, Tuple::point(2.0, 5.0, 4.0));
+        assert_eq!(&shearing(0.0, 0.0, 0.0, 1.0, 0.0, 0.0) * p, Tuple::point(2.0, 7.0, 4.0));
+        assert_eq!(&shearing(0.0, 0.0, 0.0, 0.0, 1.0, 0.0) * p, Tuple::point(2.0, 3.0, 6.0));
+        assert_eq!(&shearing(0.0, 0.0, 0.0, 0.0, 0.0, 1.0) * p, Tuple::point(2.0, 3.0, 7.0));
+    }
+
+    #[test]
+    fn test_chaining() {
+        let p = Tuple::point(1.0, 0.0, 1.0);
+        let a = rotation_x(PI / 2.0);
+        let b = scaling(5.0, 5.0, 5.0);
+        let c = translation(10.0, 5.0, 7.0);
+        let expected = Tuple::point(15.0, 0.0, 7.0);
+
+        assert_tuple_eq(&(c * b * a) * p, expected);
+
+        let t = identity()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+        assert_tuple_eq(&t * p, expected);
+    }
+
+    #[test]
+    fn test_view_transform() {
+        let t = view_transform(
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::point(0.0, 0.0, -1.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        );
+        assert_eq!(t, Matrix::identity());
+
+        let t = view_transform(
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::point(0.0, 0.0, 1.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        );
+        assert_eq!(t, scaling(-1.0, 1.0, -1.0));
+
+        let t = view_transform(
+            Tuple::point(0.0, 0.0, 8.0),
+            Tuple::point(0.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+        );
+        assert_eq!(t, translation(0.0, 0.0, -8.0));
+
+        let t = view_transform(
+            Tuple::point(1.0, 3.0, 2.0),
+            Tuple::point(4.0, -2.0, 8.0),
+            Tuple::vector(1.0, 1.0, 0.0),
+        );
+        let expected: Matrix = "matrix(-0.50709, 0.50709, 0.67612, -2.36643; 0.76772, 0.60609, 0.12122, -2.82843; -0.35857, 0.59761, -0.71714, 0.00000; 0.00000, 0.00000, 0.00000, 1.00000)"
+            .parse()
+            .unwrap();
+        assert_eq!(t, expected);
+    }
+}
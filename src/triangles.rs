@@ -0,0 +1,268 @@
+use crate::bounds::BoundingBox;
+use crate::intersections::{Intersection, Intersections};
+use crate::rays::Ray;
+use crate::shapes::{Shape, ShapeProps};
+use crate::tuples::{Tuple, EPSILON};
+
+/// A flat triangle, with its edges and normal precomputed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    pub props: ShapeProps,
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+    pub normal: Tuple,
+}
+
+/// A triangle with a normal per vertex, interpolated across its face.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothTriangle {
+    pub props: ShapeProps,
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub n1: Tuple,
+    pub n2: Tuple,
+    pub n3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+}
+
+/// Möller–Trumbore ray/triangle intersection. Returns `(t, u, v)` on a hit.
+fn intersect_triangle(ray: &Ray, p1: Tuple, e1: Tuple, e2: Tuple) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.direction.cross(&e2);
+    let det = e1.dot(&dir_cross_e2);
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = ray.origin - p1;
+    let u = f * p1_to_origin.dot(&dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(&e1);
+    let v = f * ray.direction.dot(&origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    Some((f * e2.dot(&origin_cross_e1), u, v))
+}
+
+fn triangle_bounds(p1: Tuple, p2: Tuple, p3: Tuple) -> BoundingBox {
+    let mut b = BoundingBox::empty();
+    for p in [p1, p2, p3] {
+        b.add_point(p);
+    }
+    b
+}
+
+impl Triangle {
+    pub fn triangle(p1: Tuple, p2: Tuple, p3: Tuple) -> Triangle {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        Triangle {
+            props: ShapeProps::shape_props(),
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(&e1).normalize(),
+        }
+    }
+}
+
+impl SmoothTriangle {
+    pub fn smooth_triangle(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> SmoothTriangle {
+        SmoothTriangle {
+            props: ShapeProps::shape_props(),
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        }
+    }
+}
+
+impl Shape for Triangle {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match intersect_triangle(ray, self.p1, self.e1, self.e2) {
+            Some((t, u, v)) => {
+                Intersections::intersections(vec![Intersection::intersection_with_uv(t, self, u, v)])
+            }
+            None => Intersections::default(),
+        }
+    }
+
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        self.normal
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match intersect_triangle(ray, self.p1, self.e1, self.e2) {
+            Some((t, u, v)) => {
+                Intersections::intersections(vec![Intersection::intersection_with_uv(t, self, u, v)])
+            }
+            None => Intersections::default(),
+        }
+    }
+
+    /// Without a hit there is nothing to interpolate with, so this falls back
+    /// to the normal of the face.
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        self.e2.cross(&self.e1).normalize()
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+
+    fn local_normal_at_hit(&self, _point: Tuple, hit: &Intersection) -> Tuple {
+        self.n2 * hit.u + self.n3 * hit.v + self.n1 * (1.0 - hit.u - hit.v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intersections::Intersections;
+    use crate::tuples::equal;
+
+    fn triangle() -> Triangle {
+        Triangle::triangle(
+            Tuple::point(0.0, 1.0, 0.0),
+            Tuple::point(-1.0, 0.0, 0.0),
+            Tuple::point(1.0, 0.0, 0.0),
+        )
+    }
+
+    fn smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::smooth_triangle(
+            Tuple::point(0.0, 1.0, 0.0),
+            Tuple::point(-1.0, 0.0, 0.0),
+            Tuple::point(1.0, 0.0, 0.0),
+            Tuple::vector(0.0, 1.0, 0.0),
+            Tuple::vector(-1.0, 0.0, 0.0),
+            Tuple::vector(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_fn() {
+        let t = triangle();
+        assert_eq!(t.e1, Tuple::vector(-1.0, -1.0, 0.0));
+        assert_eq!(t.e2, Tuple::vector(1.0, -1.0, 0.0));
+        assert_eq!(t.normal, Tuple::vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_normal() {
+        let t = triangle();
+        assert_eq!(t.local_normal_at(Tuple::point(0.0, 0.5, 0.0)), t.normal);
+        assert_eq!(t.local_normal_at(Tuple::point(-0.5, 0.75, 0.0)), t.normal);
+        assert_eq!(t.local_normal_at(Tuple::point(0.5, 0.25, 0.0)), t.normal);
+    }
+
+    #[test]
+    fn test_intersect_misses() {
+        let t = triangle();
+        for (o, d) in [
+            // parallel to the triangle
+            ((0.0, -1.0, -2.0), (0.0, 1.0, 0.0)),
+            // past each of the three edges
+            ((1.0, 1.0, -2.0), (0.0, 0.0, 1.0)),
+            ((-1.0, 1.0, -2.0), (0.0, 0.0, 1.0)),
+            ((0.0, -1.0, -2.0), (0.0, 0.0, 1.0)),
+        ] {
+            let r = Ray::ray(Tuple::point(o.0, o.1, o.2), Tuple::vector(d.0, d.1, d.2));
+            assert!(t.local_intersect(&r).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_intersect_hit() {
+        let t = triangle();
+        let r = Ray::ray(Tuple::point(0.0, 0.5, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = t.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].t == 2.0);
+    }
+
+    #[test]
+    fn test_smooth_fn() {
+        let t = smooth_triangle();
+        assert_eq!(t.p1, Tuple::point(0.0, 1.0, 0.0));
+        assert_eq!(t.n1, Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(t.n3, Tuple::vector(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_smooth_intersect_stores_uv() {
+        let t = smooth_triangle();
+        let r = Ray::ray(Tuple::point(-0.2, 0.3, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = t.local_intersect(&r);
+        assert!(equal(xs[0].u, 0.45));
+        assert!(equal(xs[0].v, 0.25));
+    }
+
+    #[test]
+    fn test_smooth_normal_interpolation() {
+        let t = smooth_triangle();
+        let i = Intersection::intersection_with_uv(1.0, &t, 0.45, 0.25);
+        let n = t.normal_at_hit(Tuple::point(0.0, 0.0, 0.0), &i);
+        assert!(n.approx_eq(&Tuple::vector(-0.5547, 0.83205, 0.0)));
+    }
+
+    #[test]
+    fn test_smooth_prepare_computations() {
+        let t = smooth_triangle();
+        let i = Intersection::intersection_with_uv(1.0, &t, 0.45, 0.25);
+        let r = Ray::ray(Tuple::point(-0.2, 0.3, -2.0), Tuple::vector(0.0, 0.0, 1.0));
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(comps.normalv.approx_eq(&Tuple::vector(-0.5547, 0.83205, 0.0)));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let t = Triangle::triangle(
+            Tuple::point(-3.0, 7.0, 2.0),
+            Tuple::point(6.0, 2.0, -4.0),
+            Tuple::point(2.0, -1.0, -1.0),
+        );
+        let b = t.bounds();
+        assert_eq!(b.min, Tuple::point(-3.0, -1.0, -4.0));
+        assert_eq!(b.max, Tuple::point(6.0, 7.0, 2.0));
+    }
+}
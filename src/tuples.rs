@@ -4,6 +4,13 @@ use regex::Regex;
 use std::ops::{Add, Div, Mul, Neg, Sub};
 use std::cmp::Eq;
 
+pub const EPSILON: f64 = 0.00001;
+
+/// Compares two floats allowing for rounding errors.
+pub fn equal(a: f64, b: f64) -> bool {
+    (a - b).abs() < EPSILON
+}
+
 #[derive(Debug, Default, Clone, Copy, Parameter)]
 #[param(name = "tuple", regex = r"tuple\(.+, .+, .+, .+\)")]
 pub struct Tuple {
@@ -55,6 +62,16 @@ impl Tuple {
             self.x * other.y - self.y * other.x,
         )
     }
+
+    /// Reflects the vector around `normal`.
+    pub fn reflect(&self, normal: &Tuple) -> Tuple {
+        *self - *normal * 2.0 * self.dot(normal)
+    }
+
+    /// Like `==` but tolerates rounding errors up to `EPSILON`.
+    pub fn approx_eq(&self, other: &Tuple) -> bool {
+        equal(self.x, other.x) && equal(self.y, other.y) && equal(self.z, other.z) && equal(self.w, other.w)
+    }
 }
 
 impl FromStr for Tuple {
@@ -249,4 +266,16 @@ mod tests {
         assert!(t01 == Tuple::vector(-1.0, 2.0, -1.0)); 
         assert!(t10 == Tuple::vector(1.0, -2.0, 1.0)); 
     }
+
+    #[test]
+    fn test_reflect() {
+        let v = Tuple::vector(1.0, -1.0, 0.0);
+        let n = Tuple::vector(0.0, 1.0, 0.0);
+        assert!(v.reflect(&n) == Tuple::vector(1.0, 1.0, 0.0));
+
+        let v = Tuple::vector(0.0, -1.0, 0.0);
+        let h = 2.0_f64.sqrt() / 2.0;
+        let n = Tuple::vector(h, h, 0.0);
+        assert!(v.reflect(&n).approx_eq(&Tuple::vector(1.0, 0.0, 0.0)));
+    }
 }
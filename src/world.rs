@@ -0,0 +1,425 @@
+use crate::colors::Color;
+use crate::intersections::{Computations, Intersections};
+use crate::lights::{lighting, Light, PointLight};
+use crate::rays::Ray;
+use crate::sequences::Sequence;
+use crate::shapes::Shape;
+use crate::spheres::Sphere;
+use crate::transformations::scaling;
+use crate::tuples::Tuple;
+
+/// How many bounces of reflected and refracted rays are traced by default.
+pub const MAX_DEPTH: usize = 5;
+
+#[derive(Debug)]
+pub struct World {
+    pub objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<Light>,
+    /// The recursion budget handed to `color_at` when rendering, bounding how
+    /// many times a ray may bounce between reflective or transparent surfaces.
+    pub max_depth: usize,
+}
+
+impl World {
+    pub fn world() -> World {
+        World {
+            objects: Vec::new(),
+            lights: Vec::new(),
+            max_depth: MAX_DEPTH,
+        }
+    }
+
+    /// The classic test fixture: a light up and to the left and two
+    /// concentric spheres at the origin.
+    pub fn default_world() -> World {
+        let light = PointLight::point_light(Tuple::point(-10.0, 10.0, -10.0), Color::color(1.0, 1.0, 1.0));
+
+        let mut s1 = Sphere::sphere();
+        s1.material_mut().color = Color::color(0.8, 1.0, 0.6);
+        s1.material_mut().diffuse = 0.7;
+        s1.material_mut().specular = 0.2;
+
+        let mut s2 = Sphere::sphere();
+        s2.set_transform(scaling(0.5, 0.5, 0.5));
+
+        World {
+            objects: vec![Box::new(s1), Box::new(s2)],
+            lights: vec![light.into()],
+            max_depth: MAX_DEPTH,
+        }
+    }
+
+    pub fn intersect_world(&self, ray: &Ray) -> Intersections<'_> {
+        let mut xs = Intersections::default();
+        for object in self.objects.iter() {
+            xs.extend(object.intersect(ray));
+        }
+        xs
+    }
+
+    /// The color at a hit, including whatever it reflects and lets through.
+    /// `remaining` is how many more bounces may be traced from here, and
+    /// jittered lights are sampled with `sampler`.
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize, sampler: &mut dyn Sequence) -> Color {
+        let mut surface = Color::color(0.0, 0.0, 0.0);
+        for light in self.lights.iter() {
+            let illumination = light.illuminate(comps.over_point, self, sampler);
+            surface = surface
+                + lighting(
+                    comps.object.material(),
+                    comps.object,
+                    light,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    &illumination,
+                );
+        }
+
+        let reflected = self.reflected_color(comps, remaining, sampler);
+        let refracted = self.refracted_color(comps, remaining, sampler);
+
+        let material = comps.object.material();
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    pub fn color_at(&self, ray: &Ray, remaining: usize, sampler: &mut dyn Sequence) -> Color {
+        let xs = self.intersect_world(ray);
+        match xs.hit() {
+            Some(hit) => self.shade_hit(&hit.prepare_computations(ray, &xs), remaining, sampler),
+            None => Color::color(0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize, sampler: &mut dyn Sequence) -> Color {
+        let reflective = comps.object.material().reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return Color::color(0.0, 0.0, 0.0);
+        }
+        let reflect_ray = Ray::ray(comps.over_point, comps.reflectv);
+        self.color_at(&reflect_ray, remaining - 1, sampler) * reflective
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize, sampler: &mut dyn Sequence) -> Color {
+        let transparency = comps.object.material().transparency;
+        if remaining == 0 || transparency == 0.0 {
+            return Color::color(0.0, 0.0, 0.0);
+        }
+
+        // Snell's law, solved for the angle of the refracted ray
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(&comps.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            // total internal reflection
+            return Color::color(0.0, 0.0, 0.0);
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let refract_ray = Ray::ray(comps.under_point, direction);
+        self.color_at(&refract_ray, remaining - 1, sampler) * transparency
+    }
+
+    /// Whether anything lies between `point` and a light at `light_position`.
+    pub fn is_shadowed(&self, point: Tuple, light_position: Tuple) -> bool {
+        let v = light_position - point;
+        self.is_occluded(point, v.normalize(), v.magnitude())
+    }
+
+    /// Whether anything lies within `distance` of `point` along `direction`,
+    /// which must be normalized. The distance may be infinite.
+    pub fn is_occluded(&self, point: Tuple, direction: Tuple, distance: f64) -> bool {
+        let ray = Ray::ray(point, direction);
+        match self.intersect_world(&ray).hit() {
+            Some(hit) => hit.t < distance,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intersections::Intersection;
+    use crate::patterns::TestPattern;
+    use crate::planes::Plane;
+    use crate::sequences::RandomSequence;
+    use crate::transformations::translation;
+    use std::sync::Arc;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    // none of the lights here are jittered, so any sampler will do
+    fn sampler() -> RandomSequence {
+        RandomSequence::random_sequence(0)
+    }
+
+    // the expected colors from the book are rounded to 4 or 5 decimals
+    fn assert_color_near(a: Color, b: Color) {
+        let near = |x: f64, y: f64| (x - y).abs() < 0.0001;
+        assert!(near(a.red, b.red) && near(a.green, b.green) && near(a.blue, b.blue), "{:?} != {:?}", a, b);
+    }
+
+    fn reflective_floor(w: &mut World) {
+        let mut floor = Plane::plane();
+        floor.material_mut().reflective = 0.5;
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        w.objects.push(Box::new(floor));
+    }
+
+    fn ray_at_floor() -> Ray {
+        Ray::ray(Tuple::point(0.0, 0.0, -3.0), Tuple::vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2))
+    }
+
+    #[test]
+    fn test_fn() {
+        let w = World::world();
+        assert!(w.objects.is_empty());
+        assert!(w.lights.is_empty());
+    }
+
+    #[test]
+    fn test_default_world() {
+        let w = World::default_world();
+        assert_eq!(w.lights[0].position(), Some(Tuple::point(-10.0, 10.0, -10.0)));
+        assert_eq!(w.lights[0].intensity(), Color::color(1.0, 1.0, 1.0));
+        assert_eq!(w.objects.len(), 2);
+        assert_eq!(w.objects[0].material().color, Color::color(0.8, 1.0, 0.6));
+        assert_eq!(*w.objects[1].transform(), scaling(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_intersect_world() {
+        let w = World::default_world();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = w.intersect_world(&r);
+        let ts: Vec<f64> = xs.list.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn test_shade_hit() {
+        let w = World::default_world();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let i = Intersection::intersection(4.0, w.objects[0].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()).approx_eq(&Color::color(0.38066, 0.47583, 0.2855)));
+    }
+
+    #[test]
+    fn test_shade_hit_inside() {
+        let mut w = World::default_world();
+        w.lights = vec![PointLight::point_light(Tuple::point(0.0, 0.25, 0.0), Color::color(1.0, 1.0, 1.0)).into()];
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let i = Intersection::intersection(0.5, w.objects[1].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()).approx_eq(&Color::color(0.90498, 0.90498, 0.90498)));
+    }
+
+    #[test]
+    fn test_shade_hit_in_shadow() {
+        let mut w = World::world();
+        w.lights = vec![PointLight::point_light(Tuple::point(0.0, 0.0, -10.0), Color::color(1.0, 1.0, 1.0)).into()];
+        let mut s2 = Sphere::sphere();
+        s2.set_transform(translation(0.0, 0.0, 10.0));
+        w.objects = vec![Box::new(Sphere::sphere()), Box::new(s2)];
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let i = Intersection::intersection(4.0, w.objects[1].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert_eq!(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn test_shade_hit_two_lights() {
+        let mut w = World::default_world();
+        let light = w.lights[0].clone();
+        w.lights.push(light);
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let i = Intersection::intersection(4.0, w.objects[0].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert!(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()).approx_eq(&Color::color(0.76132, 0.95166, 0.5710)));
+    }
+
+    #[test]
+    fn test_color_at() {
+        let w = World::default_world();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 1.0, 0.0));
+        assert_eq!(w.color_at(&r, MAX_DEPTH, &mut sampler()), Color::color(0.0, 0.0, 0.0));
+
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        assert!(w.color_at(&r, MAX_DEPTH, &mut sampler()).approx_eq(&Color::color(0.38066, 0.47583, 0.2855)));
+    }
+
+    #[test]
+    fn test_color_at_behind_ray() {
+        let mut w = World::default_world();
+        w.objects[0].material_mut().ambient = 1.0;
+        w.objects[1].material_mut().ambient = 1.0;
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.75), Tuple::vector(0.0, 0.0, -1.0));
+        assert_eq!(w.color_at(&r, MAX_DEPTH, &mut sampler()), w.objects[1].material().color);
+    }
+
+    #[test]
+    fn test_is_shadowed() {
+        let w = World::default_world();
+        let light = w.lights[0].position().unwrap();
+        // nothing is collinear with point and light
+        assert!(!w.is_shadowed(Tuple::point(0.0, 10.0, 0.0), light));
+        // the object is between the point and the light
+        assert!(w.is_shadowed(Tuple::point(10.0, -10.0, 10.0), light));
+        // the object is behind the light
+        assert!(!w.is_shadowed(Tuple::point(-20.0, 20.0, -20.0), light));
+        // the object is behind the point
+        assert!(!w.is_shadowed(Tuple::point(-2.0, 2.0, -2.0), light));
+    }
+
+    #[test]
+    fn test_is_occluded() {
+        let w = World::default_world();
+        let point = Tuple::point(10.0, -10.0, 10.0);
+        let direction = Tuple::vector(-1.0, 1.0, -1.0).normalize();
+        assert!(w.is_occluded(point, direction, f64::INFINITY));
+        // the spheres are further away than that
+        assert!(!w.is_occluded(point, direction, 5.0));
+        assert!(!w.is_occluded(point, -direction, f64::INFINITY));
+    }
+
+    #[test]
+    fn test_reflected_color_nonreflective() {
+        let mut w = World::default_world();
+        w.objects[1].material_mut().ambient = 1.0;
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let i = Intersection::intersection(1.0, w.objects[1].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert_eq!(w.reflected_color(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_reflected_color() {
+        let mut w = World::default_world();
+        reflective_floor(&mut w);
+        let r = ray_at_floor();
+        let i = Intersection::intersection(2.0_f64.sqrt(), w.objects[2].as_ref());
+        let comps = i.prepare_computations(&r, &Intersections::intersections(vec![i]));
+        assert_color_near(w.reflected_color(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.19032, 0.2379, 0.14274));
+        assert_color_near(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.87677, 0.92436, 0.82918));
+        // no budget left
+        assert_eq!(w.reflected_color(&comps, 0, &mut sampler()), Color::color(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_mutually_reflective_surfaces() {
+        let mut w = World::world();
+        w.lights = vec![PointLight::point_light(Tuple::point(0.0, 0.0, 0.0), Color::color(1.0, 1.0, 1.0)).into()];
+        let mut lower = Plane::plane();
+        lower.material_mut().reflective = 1.0;
+        lower.set_transform(translation(0.0, -1.0, 0.0));
+        let mut upper = Plane::plane();
+        upper.material_mut().reflective = 1.0;
+        upper.set_transform(translation(0.0, 1.0, 0.0));
+        w.objects = vec![Box::new(lower), Box::new(upper)];
+
+        // terminates, and the contributions add up
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 1.0, 0.0));
+        let c = w.color_at(&r, MAX_DEPTH, &mut sampler());
+        assert!(c.red > 0.1 * (MAX_DEPTH as f64));
+    }
+
+    #[test]
+    fn test_refracted_color_opaque() {
+        let w = World::default_world();
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(4.0, w.objects[0].as_ref()),
+            Intersection::intersection(6.0, w.objects[0].as_ref()),
+        ]);
+        let comps = xs[0].prepare_computations(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_refracted_color_max_depth() {
+        let mut w = World::default_world();
+        w.objects[0].material_mut().transparency = 1.0;
+        w.objects[0].material_mut().refractive_index = 1.5;
+        let r = Ray::ray(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(4.0, w.objects[0].as_ref()),
+            Intersection::intersection(6.0, w.objects[0].as_ref()),
+        ]);
+        let comps = xs[0].prepare_computations(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, 0, &mut sampler()), Color::color(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_refracted_color_total_internal_reflection() {
+        let mut w = World::default_world();
+        w.objects[0].material_mut().transparency = 1.0;
+        w.objects[0].material_mut().refractive_index = 1.5;
+        let r = Ray::ray(Tuple::point(0.0, 0.0, FRAC_1_SQRT_2), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(-FRAC_1_SQRT_2, w.objects[0].as_ref()),
+            Intersection::intersection(FRAC_1_SQRT_2, w.objects[0].as_ref()),
+        ]);
+        // inside the sphere, so look at the second intersection
+        let comps = xs[1].prepare_computations(&r, &xs);
+        assert_eq!(w.refracted_color(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.0, 0.0, 0.0));
+    }
+
+    fn glass_floor_and_ball(w: &mut World, reflective: f64) {
+        let mut floor = Plane::plane();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        floor.material_mut().reflective = reflective;
+        floor.material_mut().transparency = 0.5;
+        floor.material_mut().refractive_index = 1.5;
+        let mut ball = Sphere::sphere();
+        ball.material_mut().color = Color::color(1.0, 0.0, 0.0);
+        ball.material_mut().ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5));
+        w.objects.push(Box::new(floor));
+        w.objects.push(Box::new(ball));
+    }
+
+    #[test]
+    fn test_shade_hit_transparent() {
+        let mut w = World::default_world();
+        glass_floor_and_ball(&mut w, 0.0);
+        let r = ray_at_floor();
+        let xs = Intersections::intersections(vec![Intersection::intersection(2.0_f64.sqrt(), w.objects[2].as_ref())]);
+        let comps = xs[0].prepare_computations(&r, &xs);
+        assert_color_near(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.93642, 0.68642, 0.68642));
+    }
+
+    #[test]
+    fn test_shade_hit_schlick() {
+        let mut w = World::default_world();
+        glass_floor_and_ball(&mut w, 0.5);
+        let r = ray_at_floor();
+        let xs = Intersections::intersections(vec![Intersection::intersection(2.0_f64.sqrt(), w.objects[2].as_ref())]);
+        let comps = xs[0].prepare_computations(&r, &xs);
+        assert_color_near(w.shade_hit(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.93391, 0.69643, 0.69243));
+    }
+
+    #[test]
+    fn test_refracted_color() {
+        let mut w = World::default_world();
+        w.objects[0].material_mut().ambient = 1.0;
+        w.objects[0].material_mut().pattern = Some(Arc::new(TestPattern::default()));
+        w.objects[1].material_mut().transparency = 1.0;
+        w.objects[1].material_mut().refractive_index = 1.5;
+        let r = Ray::ray(Tuple::point(0.0, 0.0, 0.1), Tuple::vector(0.0, 1.0, 0.0));
+        let xs = Intersections::intersections(vec![
+            Intersection::intersection(-0.9899, w.objects[0].as_ref()),
+            Intersection::intersection(-0.4899, w.objects[1].as_ref()),
+            Intersection::intersection(0.4899, w.objects[1].as_ref()),
+            Intersection::intersection(0.9899, w.objects[0].as_ref()),
+        ]);
+        let comps = xs[2].prepare_computations(&r, &xs);
+        assert_color_near(w.refracted_color(&comps, MAX_DEPTH, &mut sampler()), Color::color(0.0, 0.99888, 0.04725));
+    }
+}
@@ -2,10 +2,18 @@ use cucumber::{World};
 mod tuples;
 mod colors;
 mod canvas;
+mod matrices;
+mod transformations;
+mod rays;
+mod world;
 
 
 fn main() {
     futures::executor::block_on(colors::ColorsWorld::run("tests/features/colors.feature"));
     futures::executor::block_on(tuples::TuplesWorld::run("tests/features/tuples.feature"));
     futures::executor::block_on(canvas::CanvasWorld::run("tests/features/canvas.feature"));
+    futures::executor::block_on(matrices::MatricesWorld::run("tests/features/matrices.feature"));
+    futures::executor::block_on(transformations::TransformationsWorld::run("tests/features/transformations.feature"));
+    futures::executor::block_on(rays::RaysWorld::run("tests/features/rays.feature"));
+    futures::executor::block_on(world::WorldWorld::run("tests/features/world.feature"));
 }
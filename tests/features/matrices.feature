@@ -0,0 +1,98 @@
+Feature: Matrices Feature
+    Scenario: Constructing and inspecting a 4x4 matrix
+        Given the following matrix M:
+            | 1    | 2    | 3    | 4    |
+            | 5.5  | 6.5  | 7.5  | 8.5  |
+            | 9    | 10   | 11   | 12   |
+            | 13.5 | 14.5 | 15.5 | 16.5 |
+            Then M[0,0] = 1
+            And M[0,3] = 4
+            And M[1,0] = 5.5
+            And M[1,2] = 7.5
+            And M[2,2] = 11
+            And M[3,0] = 13.5
+            And M[3,2] = 15.5
+
+    Scenario: A 2x2 matrix ought to be representable
+        Given M is a matrix(-3, 5; 1, -2)
+            Then M[0,0] = -3
+            And M[0,1] = 5
+            And M[1,0] = 1
+            And M[1,1] = -2
+
+    Scenario: Matrix equality with identical matrices
+        Given A is a matrix(1, 2, 3, 4; 5, 6, 7, 8; 9, 8, 7, 6; 5, 4, 3, 2)
+        And B is a matrix(1, 2, 3, 4; 5, 6, 7, 8; 9, 8, 7, 6; 5, 4, 3, 2)
+            Then A = B
+
+    Scenario: Matrix equality with different matrices
+        Given A is a matrix(1, 2, 3, 4; 5, 6, 7, 8; 9, 8, 7, 6; 5, 4, 3, 2)
+        And B is a matrix(2, 3, 4, 5; 6, 7, 8, 9; 8, 7, 6, 5; 4, 3, 2, 1)
+            Then A != B
+
+    Scenario: Multiplying two matrices
+        Given the following matrix A:
+            | 1 | 2 | 3 | 4 |
+            | 5 | 6 | 7 | 8 |
+            | 9 | 8 | 7 | 6 |
+            | 5 | 4 | 3 | 2 |
+        And the following matrix B:
+            | -2 | 1 | 2 | 3  |
+            | 3  | 2 | 1 | -1 |
+            | 4  | 3 | 6 | 5  |
+            | 1  | 2 | 7 | 8  |
+            Then A * B is the following matrix:
+                | 20 | 22 | 50  | 48  |
+                | 44 | 54 | 114 | 108 |
+                | 40 | 58 | 110 | 102 |
+                | 16 | 26 | 46  | 42  |
+
+    Scenario: A matrix multiplied by a tuple
+        Given A is a matrix(1, 2, 3, 4; 2, 4, 4, 2; 8, 6, 4, 1; 0, 0, 0, 1)
+        And b is a tuple(1, 2, 3, 1)
+            Then A * b = tuple(18, 24, 33, 1)
+
+    Scenario: Multiplying a matrix by the identity matrix
+        Given A is a matrix(0, 1, 2, 4; 1, 2, 4, 8; 2, 4, 8, 16; 4, 8, 16, 32)
+            Then A * identity_matrix = A
+
+    Scenario: Transposing a matrix
+        Given A is a matrix(0, 9, 3, 0; 9, 8, 0, 8; 1, 8, 5, 3; 0, 0, 5, 8)
+            Then transpose(A) = matrix(0, 9, 1, 0; 9, 8, 8, 0; 3, 0, 5, 5; 0, 8, 3, 8)
+
+    Scenario: Calculating the determinant of a 2x2 matrix
+        Given A is a matrix(1, 5; -3, 2)
+            Then determinant(A) = 17
+
+    Scenario: A submatrix of a 3x3 matrix is a 2x2 matrix
+        Given A is a matrix(1, 5, 0; -3, 2, 7; 0, 6, -3)
+            Then submatrix(A, 0, 2) = matrix(-3, 2; 0, 6)
+
+    Scenario: Calculating a minor and a cofactor of a 3x3 matrix
+        Given A is a matrix(3, 5, 0; 2, -1, -7; 6, -1, 5)
+            Then minor(A, 0, 0) = -12
+            And cofactor(A, 0, 0) = -12
+            And minor(A, 1, 0) = 25
+            And cofactor(A, 1, 0) = -25
+
+    Scenario: Calculating the determinant of a 4x4 matrix
+        Given A is a matrix(-2, -8, 3, 5; -3, 1, 7, 3; 1, 2, -9, 6; -6, 7, 7, -9)
+            Then cofactor(A, 0, 0) = 690
+            And cofactor(A, 0, 1) = 447
+            And cofactor(A, 0, 2) = 210
+            And cofactor(A, 0, 3) = 51
+            And determinant(A) = -4071
+
+    Scenario: Testing a noninvertible matrix for invertibility
+        Given A is a matrix(-4, 2, -2, -3; 9, 6, 2, 6; 0, -5, 1, -5; 0, 0, 0, 0)
+            Then A is not invertible
+
+    Scenario: Calculating the inverse of a matrix
+        Given A is a matrix(8, -5, 9, 2; 7, 5, 6, 1; -6, 0, 9, 6; -3, 0, -9, -4)
+            Then A is invertible
+            And inverse(A) = matrix(-0.15385, -0.15385, -0.28205, -0.53846; -0.07692, 0.12308, 0.02564, 0.03077; 0.35897, 0.35897, 0.43590, 0.92308; -0.69231, -0.69231, -0.76923, -1.92308)
+
+    Scenario: Multiplying a product by its inverse
+        Given A is a matrix(3, -9, 7, 3; 3, -8, 2, -9; -4, 4, 4, 1; -6, 5, -1, 1)
+        And B is a matrix(8, 2, 2, 2; 3, -1, 7, 0; 7, 0, 5, 4; 6, -2, 0, 5)
+            Then A * B * inverse(B) = A
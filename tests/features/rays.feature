@@ -0,0 +1,56 @@
+Feature: Rays Feature
+    Scenario: Computing a point from a distance
+        Given r is a ray from point 2, 3, 4 towards vector 1, 0, 0
+            Then position(r, 0) = point 2, 3, 4
+            And position(r, 1) = point 3, 3, 4
+            And position(r, -1) = point 1, 3, 4
+            And position(r, 2.5) = point 4.5, 3, 4
+
+    Scenario: Translating a ray
+        Given r is a ray from point 1, 2, 3 towards vector 0, 1, 0
+            When r2 is r translated by 3, 4, 5
+            Then r2.origin = point 4, 6, 8
+            And r2.direction = vector 0, 1, 0
+
+    Scenario: Scaling a ray
+        Given r is a ray from point 1, 2, 3 towards vector 0, 1, 0
+            When r2 is r scaled by 2, 3, 4
+            Then r2.origin = point 2, 6, 12
+            And r2.direction = vector 0, 3, 0
+
+    Scenario: A ray intersects a sphere at two points
+        Given r is a ray from point 0, 0, -5 towards vector 0, 0, 1
+        And s is a sphere
+            Then intersect(s, r) = 4, 6
+
+    Scenario: A ray intersects a sphere at a tangent
+        Given r is a ray from point 0, 1, -5 towards vector 0, 0, 1
+        And s is a sphere
+            Then intersect(s, r) = 5, 5
+
+    Scenario: A ray misses a sphere
+        Given r is a ray from point 0, 2, -5 towards vector 0, 0, 1
+        And s is a sphere
+            Then intersect(s, r) is empty
+
+    Scenario: A ray originates inside a sphere
+        Given r is a ray from point 0, 0, 0 towards vector 0, 0, 1
+        And s is a sphere
+            Then intersect(s, r) = -1, 1
+
+    Scenario: A sphere is behind a ray
+        Given r is a ray from point 0, 0, 5 towards vector 0, 0, 1
+        And s is a sphere
+            Then intersect(s, r) = -6, -4
+
+    Scenario: Intersecting a scaled sphere with a ray
+        Given r is a ray from point 0, 0, -5 towards vector 0, 0, 1
+        And s is a sphere
+            When s is scaled by 2, 2, 2
+            Then intersect(s, r) = 3, 7
+
+    Scenario: Intersecting a translated sphere with a ray
+        Given r is a ray from point 0, 0, -5 towards vector 0, 0, 1
+        And s is a sphere
+            When s is translated by 5, 0, 0
+            Then intersect(s, r) is empty
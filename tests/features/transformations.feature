@@ -0,0 +1,83 @@
+Feature: Transformations Feature
+    Scenario: Multiplying by a translation matrix
+        Given transform is translation(5, -3, 2)
+        And p is a point -3, 4, 5
+            Then transform * p = point 2, 1, 7
+
+    Scenario: Multiplying by the inverse of a translation matrix
+        Given transform is translation(5, -3, 2)
+        And p is a point -3, 4, 5
+            When inv is inverse(transform)
+            Then inv * p = point -8, 7, 3
+
+    Scenario: Translation does not affect vectors
+        Given transform is translation(5, -3, 2)
+        And v is a vector -3, 4, 5
+            Then transform * v = vector -3, 4, 5
+
+    Scenario: A scaling matrix applied to a point
+        Given transform is scaling(2, 3, 4)
+        And p is a point -4, 6, 8
+            Then transform * p = point -8, 18, 32
+
+    Scenario: A scaling matrix applied to a vector
+        Given transform is scaling(2, 3, 4)
+        And v is a vector -4, 6, 8
+            Then transform * v = vector -8, 18, 32
+
+    Scenario: Reflection is scaling by a negative value
+        Given transform is scaling(-1, 1, 1)
+        And p is a point 2, 3, 4
+            Then transform * p = point -2, 3, 4
+
+    Scenario: Rotating a point around the x axis
+        Given p is a point 0, 1, 0
+        And full_quarter is rotation_x(π / 2)
+            Then full_quarter * p = point 0, 0, 1
+
+    Scenario: Rotating a point around the y axis
+        Given p is a point 0, 0, 1
+        And full_quarter is rotation_y(π / 2)
+            Then full_quarter * p = point 1, 0, 0
+
+    Scenario: Rotating a point around the z axis
+        Given p is a point 0, 1, 0
+        And full_quarter is rotation_z(π / 2)
+            Then full_quarter * p = point -1, 0, 0
+
+    Scenario: A shearing transformation moves x in proportion to y
+        Given transform is shearing(1, 0, 0, 0, 0, 0)
+        And p is a point 2, 3, 4
+            Then transform * p = point 5, 3, 4
+
+    Scenario: A shearing transformation moves z in proportion to y
+        Given transform is shearing(0, 0, 0, 0, 0, 1)
+        And p is a point 2, 3, 4
+            Then transform * p = point 2, 3, 7
+
+    Scenario: Chained transformations must be applied in reverse order
+        Given p is a point 1, 0, 1
+        And A is rotation_x(π / 2)
+        And B is scaling(5, 5, 5)
+        And C is translation(10, 5, 7)
+            When T is C * B * A
+            Then T * p = point 15, 0, 7
+
+    Scenario: The fluent API applies transformations in call order
+        Given p is a point 1, 0, 1
+            When T is identity().rotate_x(π / 2).scale(5, 5, 5).translate(10, 5, 7)
+            Then T * p = point 15, 0, 7
+
+    Scenario: The transformation matrix for the default orientation
+        Given from is a point 0, 0, 0
+        And to is a point 0, 0, -1
+        And up is a vector 0, 1, 0
+            When t is view_transform(from, to, up)
+            Then t = identity_matrix
+
+    Scenario: The view transformation moves the world
+        Given from is a point 0, 0, 8
+        And to is a point 0, 0, 0
+        And up is a vector 0, 1, 0
+            When t is view_transform(from, to, up)
+            Then t = translation(0, 0, -8)
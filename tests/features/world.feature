@@ -0,0 +1,45 @@
+Feature: World Feature
+    Scenario: Creating a world
+        Given w is a world
+            Then w contains no objects
+            And w has no light source
+
+    Scenario: The default world
+        Given w is the default world
+            Then w has 2 objects
+            And w has 1 light
+
+    Scenario: Intersect a world with a ray
+        Given w is the default world
+        And r is a ray from point 0, 0, -5 towards vector 0, 0, 1
+            Then intersect_world(w, r) has 4 intersections
+            And intersection 0 of intersect_world(w, r) has t = 4
+            And intersection 1 of intersect_world(w, r) has t = 4.5
+            And intersection 2 of intersect_world(w, r) has t = 5.5
+            And intersection 3 of intersect_world(w, r) has t = 6
+
+    Scenario: The color when a ray misses
+        Given w is the default world
+        And r is a ray from point 0, 0, -5 towards vector 0, 1, 0
+            Then color_at(w, r) = color(0, 0, 0)
+
+    Scenario: The color when a ray hits
+        Given w is the default world
+        And r is a ray from point 0, 0, -5 towards vector 0, 0, 1
+            Then color_at(w, r) = color(0.38066, 0.47583, 0.2855)
+
+    Scenario: There is no shadow when nothing is collinear with point and light
+        Given w is the default world
+            Then is_shadowed(w, point 0, 10, 0) is false
+
+    Scenario: The shadow when an object is between the point and the light
+        Given w is the default world
+            Then is_shadowed(w, point 10, -10, 10) is true
+
+    Scenario: There is no shadow when an object is behind the light
+        Given w is the default world
+            Then is_shadowed(w, point -20, 20, -20) is false
+
+    Scenario: There is no shadow when an object is behind the point
+        Given w is the default world
+            Then is_shadowed(w, point -2, 2, -2) is false
@@ -0,0 +1,119 @@
+use cucumber::{given, then, World};
+use cucumber::gherkin::Step;
+use ray::{Matrix, Tuple};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, World)]
+pub struct MatricesWorld {
+    matrices: HashMap<String, Matrix>,
+    tuples: HashMap<String, Tuple>,
+}
+
+fn table_to_matrix(step: &Step) -> Matrix {
+    let table = step.table.as_ref().expect("Expected a table!");
+    let text = table
+        .rows
+        .iter()
+        .map(|r| r.join("|"))
+        .collect::<Vec<String>>()
+        .join("\n");
+    text.parse().unwrap()
+}
+
+#[given(expr = "the following matrix {word}:")]
+fn given_a_table_matrix(world: &mut MatricesWorld, step: &Step, name: String) {
+    world.matrices.insert(name, table_to_matrix(step));
+}
+
+#[given(expr = "{word} is a {matrix}")]
+fn given_a_matrix(world: &mut MatricesWorld, name: String, m: Matrix) {
+    world.matrices.insert(name, m);
+}
+
+#[given(expr = "{word} is a {tuple}")]
+fn given_a_tuple(world: &mut MatricesWorld, name: String, t: Tuple) {
+    world.tuples.insert(name, t);
+}
+
+#[then(expr = "{word}[{int},{int}] = {float}")]
+fn check_matrix_cell(world: &mut MatricesWorld, name: String, row: usize, col: usize, v: f64) {
+    let m: &Matrix = &world.matrices[&name];
+    assert!(m[(row, col)] == v);
+}
+
+#[then(regex = r"^([A-Z]) = ([A-Z])$")]
+fn check_eq(world: &mut MatricesWorld, a: String, b: String) {
+    assert_eq!(world.matrices[&a], world.matrices[&b]);
+}
+
+#[then(expr = "{word} != {word}")]
+fn check_ne(world: &mut MatricesWorld, a: String, b: String) {
+    assert_ne!(world.matrices[&a], world.matrices[&b]);
+}
+
+#[then(expr = "{word} * {word} is the following matrix:")]
+fn check_mul_table(world: &mut MatricesWorld, step: &Step, a: String, b: String) {
+    let m = &world.matrices[&a] * &world.matrices[&b];
+    assert_eq!(m, table_to_matrix(step));
+}
+
+#[then(expr = "{word} * {word} = {tuple}")]
+fn check_mul_tuple(world: &mut MatricesWorld, a: String, b: String, result: Tuple) {
+    let t = &world.matrices[&a] * world.tuples[&b];
+    assert!(t == result);
+}
+
+#[then(expr = "{word} * identity_matrix = {word}")]
+fn check_mul_identity(world: &mut MatricesWorld, a: String, b: String) {
+    let m = &world.matrices[&a] * &Matrix::identity();
+    assert_eq!(m, world.matrices[&b]);
+}
+
+#[then(expr = "transpose\\({word}\\) = {matrix}")]
+fn check_transpose(world: &mut MatricesWorld, a: String, result: Matrix) {
+    assert_eq!(world.matrices[&a].transpose(), result);
+}
+
+#[then(expr = "determinant\\({word}\\) = {float}")]
+fn check_determinant(world: &mut MatricesWorld, a: String, result: f64) {
+    assert!(world.matrices[&a].determinant() == result);
+}
+
+#[then(expr = "submatrix\\({word}, {int}, {int}\\) = {matrix}")]
+fn check_submatrix(world: &mut MatricesWorld, a: String, row: usize, col: usize, result: Matrix) {
+    assert_eq!(world.matrices[&a].submatrix(row, col), result);
+}
+
+#[then(expr = "minor\\({word}, {int}, {int}\\) = {float}")]
+fn check_minor(world: &mut MatricesWorld, a: String, row: usize, col: usize, result: f64) {
+    assert!(world.matrices[&a].minor(row, col) == result);
+}
+
+#[then(expr = "cofactor\\({word}, {int}, {int}\\) = {float}")]
+fn check_cofactor(world: &mut MatricesWorld, a: String, row: usize, col: usize, result: f64) {
+    assert!(world.matrices[&a].cofactor(row, col) == result);
+}
+
+#[then(expr = "{word} is invertible")]
+fn check_invertible(world: &mut MatricesWorld, a: String) {
+    assert!(world.matrices[&a].is_invertible());
+}
+
+#[then(expr = "{word} is not invertible")]
+fn check_not_invertible(world: &mut MatricesWorld, a: String) {
+    assert!(!world.matrices[&a].is_invertible());
+    assert!(world.matrices[&a].inverse().is_none());
+}
+
+#[then(expr = "inverse\\({word}\\) = {matrix}")]
+fn check_inverse(world: &mut MatricesWorld, a: String, result: Matrix) {
+    let inv = world.matrices[&a].inverse().unwrap();
+    // the expected values are rounded to 5 decimals
+    assert!(inv.data.iter().zip(result.data.iter()).all(|(x, y)| (x - y).abs() < 0.0001));
+}
+
+#[then(expr = "{word} * {word} * inverse\\({word}\\) = {word}")]
+fn check_product_inverse(world: &mut MatricesWorld, a: String, b: String, c: String, d: String) {
+    let m = &(&world.matrices[&a] * &world.matrices[&b]) * &world.matrices[&c].inverse().unwrap();
+    assert_eq!(m, world.matrices[&d]);
+}
@@ -0,0 +1,72 @@
+use cucumber::{given, then, when, World};
+use ray::transformations::{scaling, translation};
+use ray::{Ray, Shape, Sphere, Tuple};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, World)]
+pub struct RaysWorld {
+    rays: HashMap<String, Ray>,
+    spheres: HashMap<String, Sphere>,
+}
+
+#[allow(clippy::too_many_arguments)]
+#[given(expr = "{word} is a ray from point {float}, {float}, {float} towards vector {float}, {float}, {float}")]
+fn given_a_ray(world: &mut RaysWorld, name: String, ox: f64, oy: f64, oz: f64, dx: f64, dy: f64, dz: f64) {
+    let r = Ray::ray(Tuple::point(ox, oy, oz), Tuple::vector(dx, dy, dz));
+    world.rays.insert(name, r);
+}
+
+#[given(expr = "{word} is a sphere")]
+fn given_a_sphere(world: &mut RaysWorld, name: String) {
+    world.spheres.insert(name, Sphere::sphere());
+}
+
+#[when(expr = "{word} is {word} translated by {float}, {float}, {float}")]
+fn when_ray_translated(world: &mut RaysWorld, name: String, rname: String, x: f64, y: f64, z: f64) {
+    let r = world.rays[&rname].transform(&translation(x, y, z));
+    world.rays.insert(name, r);
+}
+
+#[when(expr = "{word} is {word} scaled by {float}, {float}, {float}")]
+fn when_ray_scaled(world: &mut RaysWorld, name: String, rname: String, x: f64, y: f64, z: f64) {
+    let r = world.rays[&rname].transform(&scaling(x, y, z));
+    world.rays.insert(name, r);
+}
+
+#[when(expr = "{word} is translated by {float}, {float}, {float}")]
+fn when_sphere_translated(world: &mut RaysWorld, name: String, x: f64, y: f64, z: f64) {
+    world.spheres.get_mut(&name).unwrap().set_transform(translation(x, y, z));
+}
+
+#[when(expr = "{word} is scaled by {float}, {float}, {float}")]
+fn when_sphere_scaled(world: &mut RaysWorld, name: String, x: f64, y: f64, z: f64) {
+    world.spheres.get_mut(&name).unwrap().set_transform(scaling(x, y, z));
+}
+
+#[then(expr = "position\\({word}, {float}\\) = point {float}, {float}, {float}")]
+fn check_position(world: &mut RaysWorld, rname: String, t: f64, x: f64, y: f64, z: f64) {
+    assert!(world.rays[&rname].position(t) == Tuple::point(x, y, z));
+}
+
+#[then(expr = "{word}.origin = point {float}, {float}, {float}")]
+fn check_origin(world: &mut RaysWorld, rname: String, x: f64, y: f64, z: f64) {
+    assert!(world.rays[&rname].origin == Tuple::point(x, y, z));
+}
+
+#[then(expr = "{word}.direction = vector {float}, {float}, {float}")]
+fn check_direction(world: &mut RaysWorld, rname: String, x: f64, y: f64, z: f64) {
+    assert!(world.rays[&rname].direction == Tuple::vector(x, y, z));
+}
+
+#[then(expr = "intersect\\({word}, {word}\\) = {float}, {float}")]
+fn check_intersect(world: &mut RaysWorld, sname: String, rname: String, t1: f64, t2: f64) {
+    let xs = world.spheres[&sname].intersect(&world.rays[&rname]);
+    assert_eq!(xs.len(), 2);
+    assert!(xs[0].t == t1);
+    assert!(xs[1].t == t2);
+}
+
+#[then(expr = "intersect\\({word}, {word}\\) is empty")]
+fn check_no_intersect(world: &mut RaysWorld, sname: String, rname: String) {
+    assert!(world.spheres[&sname].intersect(&world.rays[&rname]).is_empty());
+}
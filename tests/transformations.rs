@@ -0,0 +1,103 @@
+use cucumber::{given, then, when, World};
+use ray::transformations::*;
+use ray::{Matrix, Tuple};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+#[derive(Debug, Default, World)]
+pub struct TransformationsWorld {
+    matrices: HashMap<String, Matrix>,
+    tuples: HashMap<String, Tuple>,
+}
+
+#[given(expr = "{word} is translation\\({float}, {float}, {float}\\)")]
+fn given_a_translation(world: &mut TransformationsWorld, name: String, x: f64, y: f64, z: f64) {
+    world.matrices.insert(name, translation(x, y, z));
+}
+
+#[given(expr = "{word} is scaling\\({float}, {float}, {float}\\)")]
+fn given_a_scaling(world: &mut TransformationsWorld, name: String, x: f64, y: f64, z: f64) {
+    world.matrices.insert(name, scaling(x, y, z));
+}
+
+#[given(expr = "{word} is rotation_{word}\\(π \\/ {float}\\)")]
+fn given_a_rotation(world: &mut TransformationsWorld, name: String, axis: String, d: f64) {
+    let m = match axis.as_str() {
+        "x" => rotation_x(PI / d),
+        "y" => rotation_y(PI / d),
+        "z" => rotation_z(PI / d),
+        _ => panic!("Expected x, y or z!"),
+    };
+    world.matrices.insert(name, m);
+}
+
+#[allow(clippy::too_many_arguments)]
+#[given(expr = "{word} is shearing\\({float}, {float}, {float}, {float}, {float}, {float}\\)")]
+fn given_a_shearing(
+    world: &mut TransformationsWorld,
+    name: String,
+    xy: f64,
+    xz: f64,
+    yx: f64,
+    yz: f64,
+    zx: f64,
+    zy: f64,
+) {
+    world.matrices.insert(name, shearing(xy, xz, yx, yz, zx, zy));
+}
+
+#[given(expr = "{word} is a point {float}, {float}, {float}")]
+fn given_a_point(world: &mut TransformationsWorld, name: String, x: f64, y: f64, z: f64) {
+    world.tuples.insert(name, Tuple::point(x, y, z));
+}
+
+#[given(expr = "{word} is a vector {float}, {float}, {float}")]
+fn given_a_vector(world: &mut TransformationsWorld, name: String, x: f64, y: f64, z: f64) {
+    world.tuples.insert(name, Tuple::vector(x, y, z));
+}
+
+#[when(expr = "{word} is inverse\\({word}\\)")]
+fn when_inverse(world: &mut TransformationsWorld, name: String, m: String) {
+    let inv = world.matrices[&m].inverse().unwrap();
+    world.matrices.insert(name, inv);
+}
+
+#[when(expr = "{word} is {word} * {word} * {word}")]
+fn when_chained(world: &mut TransformationsWorld, name: String, a: String, b: String, c: String) {
+    let m = &(&world.matrices[&a] * &world.matrices[&b]) * &world.matrices[&c];
+    world.matrices.insert(name, m);
+}
+
+#[allow(clippy::too_many_arguments)]
+#[when(expr = "{word} is identity\\(\\).rotate_x\\(π \\/ {float}\\).scale\\({float}, {float}, {float}\\).translate\\({float}, {float}, {float}\\)")]
+fn when_fluent(world: &mut TransformationsWorld, name: String, r: f64, sx: f64, sy: f64, sz: f64, tx: f64, ty: f64, tz: f64) {
+    let m = identity().rotate_x(PI / r).scale(sx, sy, sz).translate(tx, ty, tz);
+    world.matrices.insert(name, m);
+}
+
+#[when(expr = "{word} is view_transform\\({word}, {word}, {word}\\)")]
+fn when_view_transform(world: &mut TransformationsWorld, name: String, from: String, to: String, up: String) {
+    let m = view_transform(world.tuples[&from], world.tuples[&to], world.tuples[&up]);
+    world.matrices.insert(name, m);
+}
+
+#[then(expr = "{word} * {word} = {word} {float}, {float}, {float}")]
+fn check_mul(world: &mut TransformationsWorld, m: String, t: String, kind: String, x: f64, y: f64, z: f64) {
+    let expected = match kind.as_str() {
+        "point" => Tuple::point(x, y, z),
+        "vector" => Tuple::vector(x, y, z),
+        _ => panic!("Expected point or vector!"),
+    };
+    let result = &world.matrices[&m] * world.tuples[&t];
+    assert!(result.approx_eq(&expected));
+}
+
+#[then(expr = "{word} = identity_matrix")]
+fn check_identity(world: &mut TransformationsWorld, m: String) {
+    assert_eq!(world.matrices[&m], Matrix::identity());
+}
+
+#[then(expr = "{word} = translation\\({float}, {float}, {float}\\)")]
+fn check_translation(world: &mut TransformationsWorld, m: String, x: f64, y: f64, z: f64) {
+    assert_eq!(world.matrices[&m], translation(x, y, z));
+}
@@ -0,0 +1,72 @@
+use cucumber::{given, then};
+use ray::{Color, RandomSequence, Ray, Tuple, World};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, cucumber::World)]
+pub struct WorldWorld {
+    worlds: HashMap<String, World>,
+    rays: HashMap<String, Ray>,
+}
+
+#[given(expr = "{word} is a world")]
+fn given_a_world(world: &mut WorldWorld, name: String) {
+    world.worlds.insert(name, World::world());
+}
+
+#[given(expr = "{word} is the default world")]
+fn given_the_default_world(world: &mut WorldWorld, name: String) {
+    world.worlds.insert(name, World::default_world());
+}
+
+#[allow(clippy::too_many_arguments)]
+#[given(expr = "{word} is a ray from point {float}, {float}, {float} towards vector {float}, {float}, {float}")]
+fn given_a_ray(world: &mut WorldWorld, name: String, ox: f64, oy: f64, oz: f64, dx: f64, dy: f64, dz: f64) {
+    let r = Ray::ray(Tuple::point(ox, oy, oz), Tuple::vector(dx, dy, dz));
+    world.rays.insert(name, r);
+}
+
+#[then(expr = "{word} contains no objects")]
+fn check_no_objects(world: &mut WorldWorld, name: String) {
+    assert!(world.worlds[&name].objects.is_empty());
+}
+
+#[then(expr = "{word} has no light source")]
+fn check_no_lights(world: &mut WorldWorld, name: String) {
+    assert!(world.worlds[&name].lights.is_empty());
+}
+
+#[then(expr = "{word} has {int} objects")]
+fn check_objects(world: &mut WorldWorld, name: String, n: usize) {
+    assert_eq!(world.worlds[&name].objects.len(), n);
+}
+
+#[then(expr = "{word} has {int} light")]
+fn check_lights(world: &mut WorldWorld, name: String, n: usize) {
+    assert_eq!(world.worlds[&name].lights.len(), n);
+}
+
+#[then(expr = "intersect_world\\({word}, {word}\\) has {int} intersections")]
+fn check_intersection_count(world: &mut WorldWorld, wname: String, rname: String, n: usize) {
+    let xs = world.worlds[&wname].intersect_world(&world.rays[&rname]);
+    assert_eq!(xs.len(), n);
+}
+
+#[then(expr = "intersection {int} of intersect_world\\({word}, {word}\\) has t = {float}")]
+fn check_intersection_t(world: &mut WorldWorld, i: usize, wname: String, rname: String, t: f64) {
+    let xs = world.worlds[&wname].intersect_world(&world.rays[&rname]);
+    assert!(xs[i].t == t);
+}
+
+#[then(expr = "color_at\\({word}, {word}\\) = {color}")]
+fn check_color_at(world: &mut WorldWorld, wname: String, rname: String, c: Color) {
+    let w = &world.worlds[&wname];
+    let result = w.color_at(&world.rays[&rname], w.max_depth, &mut RandomSequence::random_sequence(0));
+    assert!(result.approx_eq(&c));
+}
+
+#[then(expr = "is_shadowed\\({word}, point {float}, {float}, {float}\\) is {word}")]
+fn check_is_shadowed(world: &mut WorldWorld, wname: String, x: f64, y: f64, z: f64, expected: String) {
+    let w = &world.worlds[&wname];
+    let shadowed = w.is_shadowed(Tuple::point(x, y, z), w.lights[0].position().unwrap());
+    assert_eq!(shadowed, expected == "true");
+}